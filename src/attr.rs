@@ -0,0 +1,238 @@
+use proc_macro::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+
+use crate::error::{Error, Result};
+use crate::tokens::{TokenTreeExt, ident, parenthesized, punct, split_commas};
+
+/// An outer attribute `#[...]`.
+#[derive(Clone)]
+pub(crate) struct Attribute {
+    pub pound: TokenTree,
+    pub group: Group,
+}
+
+impl Attribute {
+    /// Create `#[tokens]`.
+    pub fn new(tokens: TokenStream, span: Span) -> Self {
+        let mut group = Group::new(Delimiter::Bracket, tokens);
+        group.set_span(span);
+        Attribute {
+            pound: punct('#', span),
+            group,
+        }
+    }
+
+    /// Name of the attribute if its path is a single identifier.
+    pub fn name(&self) -> Option<String> {
+        let mut iter = self.group.stream().into_iter();
+        match (iter.next(), iter.next()) {
+            (Some(TokenTree::Ident(name)), next) if !next.as_ref().is_some_and(|tt| tt.is(':')) => {
+                Some(name.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// `#[doc(hidden)]`.
+    pub fn doc_hidden(span: Span) -> Self {
+        let tokens = [ident("doc", span), parenthesized(ident("hidden", span).into(), span)];
+        Attribute::new(tokens.into_iter().collect(), span)
+    }
+
+    /// `#[allow(lints)]`, `lints` is the parenthesized list.
+    pub fn allow(lints: &Group) -> Self {
+        let span = lints.span();
+        let tokens = [ident("allow", span), TokenTree::Group(lints.clone())];
+        Attribute::new(tokens.into_iter().collect(), span)
+    }
+
+    /// `#[deny(lint)]`.
+    pub fn deny(lint: &str, span: Span) -> Self {
+        let tokens = [ident("deny", span), parenthesized(ident(lint, span).into(), span)];
+        Attribute::new(tokens.into_iter().collect(), span)
+    }
+
+    pub fn span(&self) -> Span {
+        self.pound.span()
+    }
+
+    /// The parenthesized arguments of `#[name(...)]`.
+    pub fn args(&self) -> Option<Group> {
+        match self.group.stream().into_iter().nth(1) {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => Some(g),
+            _ => None,
+        }
+    }
+
+    /// `#[cfg]` or `#[cfg_attr]`.
+    pub fn is_cfg(&self) -> bool {
+        matches!(self.name().as_deref(), Some("cfg" | "cfg_attr"))
+    }
+
+    /// Attributes that only apply to a function with a body, e.g. `#[inline]`.
+    pub fn is_codegen(&self) -> bool {
+        matches!(
+            self.name().as_deref(),
+            Some("inline" | "cold" | "track_caller" | "no_mangle")
+        )
+    }
+
+    /// `#[expect(...)]`, only fulfilled by the impl, which has the default.
+    pub fn is_expect(&self) -> bool {
+        self.name().as_deref() == Some("expect")
+    }
+
+    /// `#[must_use]`, not allowed on the items of an impl of a trait.
+    pub fn is_must_use(&self) -> bool {
+        self.name().as_deref() == Some("must_use")
+    }
+
+    /// `#[deprecated]`, which has no effect on the items of an impl of a trait and is denied there.
+    pub fn is_deprecated(&self) -> bool {
+        self.name().as_deref() == Some("deprecated")
+    }
+
+    pub fn is_blanket(&self) -> bool {
+        self.name().as_deref() == Some("blanket")
+    }
+
+    /// Parse the entries of a `#[blanket(...)]` attribute.
+    pub fn directives(&self) -> Result<Vec<Directive>> {
+        let mut iter = self.group.stream().into_iter().skip(1);
+        let args = match (iter.next(), iter.next()) {
+            (Some(TokenTree::Group(g)), None) if g.delimiter() == Delimiter::Parenthesis => g,
+            _ => return Err(Error::new(self.span(), "expected `#[blanket(...)]`")),
+        };
+        split_commas(args.stream()).into_iter().map(Directive::parse).collect()
+    }
+}
+
+pub(crate) fn attrs_to_tokens(attrs: &[Attribute], tokens: &mut Vec<TokenTree>) {
+    for attr in attrs {
+        tokens.push(attr.pound.clone());
+        tokens.push(TokenTree::Group(attr.group.clone()));
+    }
+}
+
+/// Parse leading outer attributes from a token iterator.
+pub(crate) fn parse_outer(iter: &mut std::iter::Peekable<impl Iterator<Item = TokenTree>>) -> Result<Vec<Attribute>> {
+    let mut attrs = Vec::new();
+    while let Some(pound) = iter.next_if(|tt| tt.is('#')) {
+        match iter.next() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+                attrs.push(Attribute { pound, group })
+            }
+            _ => return Err(Error::new(pound.span(), "expected `[` after `#`")),
+        }
+    }
+    Ok(attrs)
+}
+
+/// Remove all `#[blanket(...)]` attributes and return their entries.
+pub(crate) fn take_directives(attrs: &mut Vec<Attribute>) -> Result<Vec<Directive>> {
+    let mut directives = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.is_blanket()) {
+        directives.extend(attr.directives()?);
+    }
+    attrs.retain(|attr| !attr.is_blanket());
+    Ok(directives)
+}
+
+/// A single entry in a `#[blanket(...)]` attribute.
+pub(crate) struct Directive {
+    pub name: Ident,
+    pub value: DirectiveValue,
+}
+
+pub(crate) enum DirectiveValue {
+    /// `name`
+    Flag,
+    /// `name = value`
+    Assign(Vec<TokenTree>),
+    /// `name(...)`
+    List(Group),
+}
+
+impl Directive {
+    fn parse(tokens: Vec<TokenTree>) -> Result<Self> {
+        let mut iter = tokens.into_iter();
+        let name = match iter.next() {
+            Some(TokenTree::Ident(name)) => name,
+            Some(tt) => return Err(Error::new(tt.span(), "expected a `blanket` directive")),
+            None => unreachable!("empty segments are skipped"),
+        };
+        let value = match iter.next() {
+            None => DirectiveValue::Flag,
+            Some(tt) if tt.is('=') => {
+                let value: Vec<_> = iter.collect();
+                if value.is_empty() {
+                    return Err(Error::new(tt.span(), format!("expected a value for `{name}`")));
+                }
+                return Ok(Directive {
+                    name,
+                    value: DirectiveValue::Assign(value),
+                });
+            }
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => DirectiveValue::List(g),
+            Some(tt) => return Err(Error::new(tt.span(), "expected `=`, `(` or `,`")),
+        };
+        if let Some(tt) = iter.next() {
+            return Err(Error::new(tt.span(), "expected `,`"));
+        }
+        Ok(Directive { name, value })
+    }
+
+    pub fn span(&self) -> Span {
+        self.name.span()
+    }
+
+    /// Error if this directive carries a value.
+    pub fn expect_flag(&self) -> Result<()> {
+        let span = match &self.value {
+            DirectiveValue::Flag => return Ok(()),
+            DirectiveValue::Assign(value) => value[0].span(),
+            DirectiveValue::List(list) => list.span(),
+        };
+        Err(Error::new(span, format!("`{}` does not take a value", self.name)))
+    }
+
+    /// The identifier of `name = ident`.
+    pub fn expect_ident(&self) -> Result<Ident> {
+        match &self.value {
+            DirectiveValue::Assign(value) => match value.as_slice() {
+                [TokenTree::Ident(ident)] => Ok(ident.clone()),
+                _ => Err(Error::new(value[0].span(), "expected an identifier")),
+            },
+            _ => Err(Error::new(self.span(), format!("expected `{} = ...`", self.name))),
+        }
+    }
+
+    /// The string literal of `name = "..."`, or a raw string `name = r"..."`.
+    pub fn expect_str(&self) -> Result<TokenTree> {
+        match &self.value {
+            DirectiveValue::Assign(value) => match value.as_slice() {
+                [lit @ TokenTree::Literal(_)] if lit.to_string().trim_start_matches(['r', '#']).starts_with('"') => {
+                    Ok(lit.clone())
+                }
+                _ => Err(Error::new(value[0].span(), "expected a string literal")),
+            },
+            _ => Err(Error::new(self.span(), format!("expected `{} = \"...\"`", self.name))),
+        }
+    }
+
+    /// Tokens of the directive, e.g. `name = value`.
+    pub fn to_tokens(&self, tokens: &mut Vec<TokenTree>) {
+        tokens.push(TokenTree::Ident(self.name.clone()));
+        match &self.value {
+            DirectiveValue::Flag => (),
+            DirectiveValue::Assign(value) => {
+                tokens.push(punct('=', self.span()));
+                tokens.extend(value.iter().cloned());
+            }
+            DirectiveValue::List(list) => tokens.push(TokenTree::Group(list.clone())),
+        }
+    }
+
+    pub fn unknown(&self) -> Error {
+        Error::new(self.span(), format!("unknown `blanket` directive `{}`", self.name))
+    }
+}
@@ -0,0 +1,177 @@
+use proc_macro::{Ident, Span, TokenStream, TokenTree};
+
+use crate::attr::{Attribute, attrs_to_tokens};
+use crate::error::{Error, Result};
+use crate::item::ItemTrait;
+use crate::tokens::{braced, ident, parenthesized, parse_at, punct, semi};
+
+/// Generate a `Box<dyn Trait>` wrapper with `From`, `Deref` and `DerefMut` implementations, `types` are the
+/// associated types `dyn Trait` must specify, which become type parameters of the wrapper.
+pub(crate) fn boxed(
+    item: &ItemTrait,
+    name: &Ident,
+    types: &[String],
+    cfgs: &[Attribute],
+    auto_derived: bool,
+) -> Result<TokenStream> {
+    if let Some(tt) = item.generics.first() {
+        return Err(Error::new(
+            tt.span(),
+            "`boxed` requires a trait without generic parameters",
+        ));
+    }
+    let span = name.span();
+    let mut cfg = Vec::new();
+    attrs_to_tokens(cfgs, &mut cfg);
+    let mut attrs = cfg.clone();
+    if auto_derived {
+        attrs.extend(parse_at("#[automatically_derived]", span));
+    }
+    let (params, bound) = object_bound(item, types, span);
+    let dyn_trait: Vec<_> = [ident("dyn", span)].into_iter().chain(bound.iter().cloned()).collect();
+    let wrapper: Vec<_> = [TokenTree::Ident(name.clone())].into_iter().chain(params.iter().cloned()).collect();
+    let boxed_dyn: Vec<_> = parse_at("::std::boxed::Box<", span)
+        .into_iter()
+        .chain(dyn_trait.iter().cloned())
+        .chain([punct('>', span)])
+        .collect();
+
+    let mut tokens = cfg;
+    tokens.extend(item.vis.iter().cloned());
+    tokens.push(ident("struct", span));
+    tokens.extend(wrapper.iter().cloned());
+    tokens.push(parenthesized(boxed_dyn.into_iter().collect(), span));
+    tokens.push(semi(span));
+
+    // impl<__T: Trait<..> + 'static, __Name, ..> From<__T> for Wrapper<__Name, ..>
+    tokens.extend(attrs.iter().cloned());
+    tokens.extend(parse_at("impl<__T:", span));
+    tokens.extend(bound);
+    tokens.extend(parse_at("+ 'static", span));
+    if let Some((_, list)) = params.split_first() {
+        tokens.push(punct(',', span));
+        tokens.extend(list.iter().cloned());
+    } else {
+        tokens.push(punct('>', span));
+    }
+    tokens.extend(parse_at("::core::convert::From<__T> for", span));
+    tokens.extend(wrapper.iter().cloned());
+    let from = parse_at("fn from(value: __T) -> Self { Self(::std::boxed::Box::new(value)) }", span);
+    tokens.push(braced(from.into_iter().collect(), span));
+
+    // impl<__Name, ..> Deref for Wrapper<__Name, ..>
+    tokens.extend(attrs.iter().cloned());
+    tokens.push(ident("impl", span));
+    tokens.extend(params.iter().cloned());
+    tokens.extend(parse_at("::core::ops::Deref for", span));
+    tokens.extend(wrapper.iter().cloned());
+    let mut deref = parse_at("type Target =", span);
+    deref.extend(dyn_trait);
+    deref.extend(parse_at("; fn deref(&self) -> &Self::Target { &*self.0 }", span));
+    tokens.push(braced(deref.into_iter().collect(), span));
+
+    tokens.extend(attrs);
+    tokens.push(ident("impl", span));
+    tokens.extend(params.iter().cloned());
+    tokens.extend(parse_at("::core::ops::DerefMut for", span));
+    tokens.extend(wrapper);
+    let deref_mut = parse_at("fn deref_mut(&mut self) -> &mut Self::Target { &mut *self.0 }", span);
+    tokens.push(braced(deref_mut.into_iter().collect(), span));
+    Ok(tokens.into_iter().collect())
+}
+
+/// `<__Name, ..>` and `Trait<Name = __Name, ..>` for the associated types `dyn Trait` must specify,
+/// both empty of `<>` if there are none.
+fn object_bound(item: &ItemTrait, types: &[String], span: Span) -> (Vec<TokenTree>, Vec<TokenTree>) {
+    let mut params = Vec::new();
+    let mut bound = vec![TokenTree::Ident(item.ident.clone())];
+    if !types.is_empty() {
+        let params_list: Vec<_> = types.iter().map(|name| format!("__{name}")).collect();
+        let bindings: Vec<_> = types.iter().map(|name| format!("{name} = __{name}")).collect();
+        params = parse_at(&format!("<{}>", params_list.join(", ")), span);
+        bound.extend(parse_at(&format!("<{}>", bindings.join(", ")), span));
+    }
+    (params, bound)
+}
+
+/// Generate `fn name(value: impl Trait) -> Box<dyn Trait>`, `types` are the associated types `dyn Trait` must specify.
+pub(crate) fn into_box(item: &ItemTrait, name: &Ident, types: &[String], cfgs: &[Attribute]) -> Result<TokenStream> {
+    if let Some(tt) = item.generics.first() {
+        return Err(Error::new(
+            tt.span(),
+            "`into_box` requires a trait without generic parameters",
+        ));
+    }
+    let span = name.span();
+    let (params, bound) = object_bound(item, types, span);
+    let mut tokens = Vec::new();
+    attrs_to_tokens(cfgs, &mut tokens);
+    tokens.extend(item.vis.iter().cloned());
+    tokens.extend([ident("fn", span), TokenTree::Ident(name.clone())]);
+    // <__Boxed: Trait<..> + 'static, __Name, ..>
+    tokens.extend(parse_at("<__Boxed:", span));
+    tokens.extend(bound.iter().cloned());
+    tokens.extend(parse_at("+ 'static", span));
+    if let Some((_, list)) = params.split_first() {
+        tokens.push(punct(',', span));
+        tokens.extend(list.iter().cloned());
+    } else {
+        tokens.push(punct('>', span));
+    }
+    tokens.push(parenthesized(parse_at("value: __Boxed", span).into_iter().collect(), span));
+    tokens.extend(parse_at("-> ::std::boxed::Box<dyn", span));
+    tokens.extend(bound);
+    tokens.push(punct('>', span));
+    tokens.push(braced(parse_at("::std::boxed::Box::new(value)", span).into_iter().collect(), span));
+    Ok(tokens.into_iter().collect())
+}
+
+/// Assert that `dyn Trait` is a valid type, `types` are the associated types it must specify.
+pub(crate) fn assert_object_safe(item: &ItemTrait, types: &[String], cfgs: &[Attribute]) -> Result<TokenStream> {
+    if let Some(tt) = item.generics.first() {
+        return Err(Error::new(
+            tt.span(),
+            "`object_safe` requires a trait without generic parameters",
+        ));
+    }
+    let span = item.ident.span();
+    let (params, bound) = object_bound(item, types, span);
+    // fn assert_object_safe<__Name, ..>(_: &dyn Trait<Name = __Name, ..>) {}
+    let mut check = parse_at("#[allow(dead_code)] fn assert_object_safe", span);
+    check.extend(params);
+    let mut arg = parse_at("_: &dyn", span);
+    arg.extend(bound);
+    check.push(parenthesized(arg.into_iter().collect(), span));
+    check.push(braced(TokenStream::new(), span));
+    let mut tokens = Vec::new();
+    attrs_to_tokens(cfgs, &mut tokens);
+    tokens.extend(parse_at("const _: () =", span));
+    tokens.extend([braced(check.into_iter().collect(), span), semi(span)]);
+    Ok(tokens.into_iter().collect())
+}
+
+/// Assert that `ty` implements the trait, the error points at `ty` if it does not.
+pub(crate) fn assert_impl(item: &ItemTrait, ty: &[TokenTree], cfgs: &[Attribute]) -> Result<TokenStream> {
+    if let Some(tt) = item.generics.first() {
+        return Err(Error::new(
+            tt.span(),
+            "`assert_impl` requires a trait without generic parameters",
+        ));
+    }
+    let mut cfg = Vec::new();
+    attrs_to_tokens(cfgs, &mut cfg);
+    let cfg: TokenStream = cfg.into_iter().collect();
+    let ident = &item.ident;
+    let span = ident.span();
+    // `assert_impl::<Type>()` keeps the spans of `Type` for the error.
+    let mut body: TokenStream = format!("fn assert_impl<__T: ?Sized + {ident}>() {{}} assert_impl::<")
+        .parse()
+        .expect("generated code is valid");
+    body.extend(ty.iter().cloned());
+    body.extend(">();".parse::<TokenStream>().expect("generated code is valid"));
+    let mut tokens: TokenStream = format!("{cfg} const _: fn() = ||")
+        .parse()
+        .expect("generated code is valid");
+    tokens.extend([braced(body, span), semi(span)]);
+    Ok(tokens)
+}
@@ -0,0 +1,30 @@
+use proc_macro::{Ident, Span, TokenStream};
+
+use crate::error::{Error, Result};
+
+/// Write the formatted expansion to `target/blanket_trait/{Trait}.rs`, errors are reported at `span`.
+#[cfg(feature = "debug-output")]
+pub(crate) fn write(ident: &Ident, span: Span, tokens: &TokenStream) -> Result<()> {
+    let code = match syn::parse_file(&tokens.to_string()) {
+        Ok(file) => prettyplease::unparse(&file),
+        // Still write something useful if the output does not parse.
+        Err(_) => tokens.to_string(),
+    };
+    let target = match std::env::var_os("CARGO_TARGET_DIR") {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => std::path::PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default()).join("target"),
+    };
+    let dir = target.join("blanket_trait");
+    let path = dir.join(format!("{ident}.rs"));
+    std::fs::create_dir_all(&dir)
+        .and_then(|()| std::fs::write(&path, code))
+        .map_err(|e| Error::new(span, format!("failed to write `{}`: {e}", path.display())))
+}
+
+#[cfg(not(feature = "debug-output"))]
+pub(crate) fn write(_: &Ident, span: Span, _: &TokenStream) -> Result<()> {
+    Err(Error::new(
+        span,
+        "`#[blanket(debug)]` requires the `debug-output` feature of `blanket_trait`",
+    ))
+}
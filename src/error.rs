@@ -0,0 +1,37 @@
+use proc_macro::{Delimiter, Group, Literal, Span, TokenStream, TokenTree};
+
+use crate::tokens::{ident, path_sep, punct};
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+/// A compile error reported at a span.
+pub(crate) struct Error {
+    span: Span,
+    message: String,
+}
+
+impl Error {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Error {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// Expand to `::core::compile_error! { "message" }` at the error's span.
+    pub fn into_compile_error(self) -> TokenStream {
+        let span = self.span;
+        let mut message = TokenTree::Literal(Literal::string(&self.message));
+        message.set_span(span);
+        let mut body = TokenTree::Group(Group::new(Delimiter::Brace, message.into()));
+        body.set_span(span);
+        let mut tokens = Vec::new();
+        tokens.extend(path_sep(span));
+        tokens.push(ident("core", span));
+        tokens.extend(path_sep(span));
+        tokens.push(ident("compile_error", span));
+        tokens.push(punct('!', span));
+        tokens.push(body);
+        tokens.into_iter().collect()
+    }
+}
@@ -0,0 +1,469 @@
+use proc_macro::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+
+use crate::attr::{Attribute, attrs_to_tokens, parse_outer};
+use crate::error::{Error, Result};
+use crate::item::ItemTrait;
+use crate::tokens::{
+    AngleDepth, TokenTreeExt, angle_bracketed, generic_name, generic_names, ident, idents, lifetime, param_names,
+    parenthesized, punct, split_commas,
+};
+
+/// The `impl<...> Trait for Type where ...` passed to the attribute.
+#[derive(Clone)]
+pub(crate) struct ImplHeader {
+    pub attrs: Vec<Attribute>,
+    /// `default` of a specializable impl.
+    pub defaultness: Option<TokenTree>,
+    pub unsafety: Option<TokenTree>,
+    pub impl_token: TokenTree,
+    /// `<...>`, empty if the impl is not generic.
+    pub generics: Vec<TokenTree>,
+    pub path: Vec<TokenTree>,
+    pub for_token: TokenTree,
+    pub self_ty: Vec<TokenTree>,
+    /// `where ...`, empty if there is no where clause.
+    pub where_clause: Vec<TokenTree>,
+}
+
+/// Error for a header without `for`, pointing at the start of the self type if it follows the trait.
+fn missing_for(impl_token: &TokenTree, path: &[TokenTree]) -> Error {
+    let mut depth = AngleDepth::default();
+    let mut after_segment = false;
+    for (i, tt) in path.iter().enumerate() {
+        let starts_type = matches!(tt, TokenTree::Ident(_)) || tt.is('&');
+        if depth.is_top() && after_segment && starts_type {
+            return Error::new(tt.span(), "expected `for` between the trait and the self type");
+        }
+        depth.push(tt);
+        let closes_generics = tt.is('>') && !(i > 0 && path[i - 1].is('-'));
+        after_segment = depth.is_top() && (matches!(tt, TokenTree::Ident(_)) || closes_generics);
+    }
+    let span = path.last().unwrap_or(impl_token).span();
+    Error::new(span, "expected `for` after the trait")
+}
+
+impl ImplHeader {
+    /// Parse a comma separated list of headers.
+    pub fn parse_list(tokens: TokenStream) -> Result<Vec<Self>> {
+        let mut headers = Vec::new();
+        let mut current = Vec::new();
+        let mut depth = AngleDepth::default();
+        let mut iter = tokens.into_iter().peekable();
+        while let Some(tt) = iter.next() {
+            // A comma only separates headers if another header follows,
+            // otherwise it belongs to the where clause.
+            if depth.is_top()
+                && tt.is(',')
+                && iter.peek().is_some_and(|next| {
+                    ["impl", "unsafe", "default"].iter().any(|kw| next.is_ident(kw)) || next.is('#')
+                })
+            {
+                headers.extend(Self::parse(std::mem::take(&mut current).into_iter().collect())?.for_each_type());
+                continue;
+            }
+            depth.push(&tt);
+            current.push(tt);
+        }
+        // A trailing comma after the last header.
+        if depth.is_top() && current.last().is_some_and(|tt| tt.is(',')) {
+            current.pop();
+        }
+        headers.extend(Self::parse(current.into_iter().collect())?.for_each_type());
+        Ok(headers)
+    }
+
+    /// Split `impl Trait for A, B, C` into an impl for each type.
+    fn for_each_type(self) -> Vec<Self> {
+        let types = split_commas(self.self_ty.iter().cloned().collect());
+        if types.len() < 2 {
+            return vec![self];
+        }
+        types
+            .into_iter()
+            .map(|self_ty| ImplHeader {
+                self_ty,
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    fn parse(tokens: TokenStream) -> Result<Self> {
+        let mut iter = tokens.into_iter().peekable();
+        let attrs = parse_outer(&mut iter)?;
+        let defaultness = iter.next_if(|tt| tt.is_ident("default"));
+        let unsafety = iter.next_if(|tt| tt.is_ident("unsafe"));
+        let impl_token = match iter.next() {
+            Some(tt) if tt.is_ident("impl") => tt,
+            Some(tt) => return Err(Error::new(tt.span(), "expected `impl`")),
+            None => return Err(Error::new(Span::call_site(), "expected `impl`")),
+        };
+        let mut generics = Vec::new();
+        if iter.peek().is_some_and(|tt| tt.is('<')) {
+            let mut depth = AngleDepth::default();
+            for tt in iter.by_ref() {
+                depth.push(&tt);
+                generics.push(tt);
+                if depth.is_top() {
+                    break;
+                }
+            }
+        }
+        let mut path = Vec::new();
+        let mut depth = AngleDepth::default();
+        let for_token = loop {
+            match iter.next() {
+                Some(tt) if depth.is_top() && tt.is_ident("for") => break tt,
+                Some(tt) => {
+                    depth.push(&tt);
+                    path.push(tt);
+                }
+                None => return Err(missing_for(&impl_token, &path)),
+            }
+        };
+        if path.is_empty() {
+            return Err(Error::new(
+                for_token.span(),
+                "expected a trait before `for`, e.g. `impl<T: Base> Trait for T`",
+            ));
+        }
+        if let [TokenTree::Ident(name)] = path.as_slice()
+            && generic_names(&generics).contains(&name.to_string())
+        {
+            return Err(Error::new(
+                name.span(),
+                format!("expected a trait, found type parameter `{name}`; did you write the type before the trait?"),
+            ));
+        }
+        let mut self_ty = Vec::new();
+        let mut where_clause = Vec::new();
+        let mut depth = AngleDepth::default();
+        for tt in iter {
+            depth.push(&tt);
+            if (depth.is_top() && tt.is_ident("where")) || !where_clause.is_empty() {
+                where_clause.push(tt);
+            } else {
+                self_ty.push(tt);
+            }
+        }
+        if self_ty.is_empty() {
+            return Err(Error::new(
+                for_token.span(),
+                "expected a type after `for`, e.g. `impl<T: Base> Trait for T`",
+            ));
+        }
+        Ok(ImplHeader {
+            attrs,
+            defaultness,
+            unsafety,
+            impl_token,
+            generics,
+            path,
+            for_token,
+            self_ty,
+            where_clause,
+        })
+    }
+
+    /// Names of the type parameters of the impl.
+    pub fn type_params(&self) -> Vec<String> {
+        let params = angle_bracketed(&self.generics);
+        let params: Vec<_> = params
+            .into_iter()
+            .filter(|param| !param[0].is('\'') && !param[0].is_ident("const"))
+            .collect();
+        param_names(&params)
+    }
+
+    /// Error if a type parameter of the impl is used neither by the self type, the trait
+    /// nor an associated type binding, e.g. `impl<T: A> B for u32`.
+    pub fn check_params_used(&self) -> Result<()> {
+        let mut used = idents(&[self.path.as_slice(), &self.self_ty].concat());
+        // `I: Iterator<Item = U>` constrains `U` through the projection.
+        used.extend(binding_idents(&[self.generics.as_slice(), &self.where_clause].concat()));
+        for param in angle_bracketed(&self.generics) {
+            if param[0].is('\'') || param[0].is_ident("const") {
+                continue;
+            }
+            if let Some((name, span)) = generic_name(&param)
+                && !used.contains(&name)
+            {
+                return Err(Error::new(
+                    span,
+                    format!(
+                        "type parameter `{name}` is declared but never used in the impl target, \
+                         use `#[blanket(allow_unused_generic)]` if a bound constrains it"
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Type parameters of the impl without bounds in its generics or where clause.
+    pub fn unbounded_type_params(&self) -> Vec<String> {
+        let mut bounded = Vec::new();
+        for param in angle_bracketed(&self.generics) {
+            if param.len() > 2 && param[1].is(':') {
+                bounded.extend(generic_name(&param).map(|(name, _)| name));
+            }
+        }
+        let predicates = self.where_clause.iter().skip(1).cloned().collect();
+        for predicate in split_commas(predicates) {
+            // Any predicate mentioning the parameter before its `:` may bound it.
+            let mut depth = AngleDepth::default();
+            let bounded_ty = predicate.iter().take_while(|tt| {
+                depth.push(tt);
+                !(depth.is_top() && tt.is(':'))
+            });
+            bounded.extend(bounded_ty.filter_map(|tt| match tt {
+                TokenTree::Ident(ident) => Some(ident.to_string()),
+                _ => None,
+            }));
+        }
+        let mut params = self.type_params();
+        params.retain(|param| !bounded.contains(param));
+        params
+    }
+
+    /// The only trait bound of the only type parameter, e.g. `A` in `impl<T: A> B for T`.
+    pub fn single_bound(&self) -> Option<Vec<TokenTree>> {
+        let [param] = self.type_params().try_into().ok()?;
+        let mut bounds = Vec::new();
+        let generics = angle_bracketed(&self.generics)
+            .into_iter()
+            .filter(|tokens| tokens.len() > 2 && tokens[0].is_ident(&param) && tokens[1].is(':'))
+            .map(|tokens| tokens[2..].to_vec());
+        let predicates = split_commas(self.where_clause.iter().skip(1).cloned().collect())
+            .into_iter()
+            .filter(|tokens| tokens.len() > 2 && tokens[0].is_ident(&param) && tokens[1].is(':'))
+            .map(|tokens| tokens[2..].to_vec());
+        for bound_list in generics.chain(predicates) {
+            let mut depth = AngleDepth::default();
+            let mut current: Vec<TokenTree> = Vec::new();
+            for tt in bound_list.into_iter().chain([punct('+', Span::call_site())]) {
+                if depth.is_top() && tt.is('+') {
+                    // Lifetimes and `?Sized` are not traits to re-export.
+                    if !current.is_empty() && !current[0].is('\'') && !current[0].is('?') {
+                        bounds.push(std::mem::take(&mut current));
+                    }
+                    current.clear();
+                    continue;
+                }
+                depth.push(&tt);
+                current.push(tt);
+            }
+        }
+        let [bound] = bounds.try_into().ok()?;
+        // Only the path, without generic arguments.
+        let path: Vec<_> = bound
+            .into_iter()
+            .take_while(|tt| matches!(tt, TokenTree::Ident(_)) || tt.is(':'))
+            .collect();
+        (!path.is_empty() && !path[0].is_ident("for")).then_some(path)
+    }
+
+    /// Error if the generic arguments of the implemented trait use a lifetime or
+    /// one of the trait's generic parameters that the impl does not declare.
+    pub fn check_trait_args(&self, item: &ItemTrait) -> Result<()> {
+        let declared = generic_names(&self.generics);
+        let trait_params = generic_names(&item.generics);
+        for arg in angle_bracketed(self.last_segment().get(1..).unwrap_or_default()) {
+            let Some((name, span)) = generic_name(&arg) else {
+                continue;
+            };
+            let is_lifetime = name.starts_with('\'') && name != "'static" && name != "'_";
+            let is_param = arg.len() == 1 && trait_params.contains(&name);
+            if (is_lifetime || is_param) && !declared.contains(&name) {
+                return Err(Error::new(
+                    span,
+                    format!("`{name}` is not declared by the impl, add it to the impl generics: `impl<{name}, ...>`"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// A `#[cfg]` that holds if any of the headers is enabled, `None` if one of them is unconditional.
+    pub fn cfg_any(headers: &[ImplHeader], span: Span) -> Option<Attribute> {
+        let mut any = Vec::new();
+        for header in headers {
+            let mut all = Vec::new();
+            let cfgs = header.attrs.iter().filter(|attr| attr.name().as_deref() == Some("cfg"));
+            for args in cfgs.filter_map(Attribute::args) {
+                all.extend(args.stream());
+                all.push(punct(',', span));
+            }
+            if all.is_empty() {
+                return None;
+            }
+            any.push(ident("all", span));
+            any.push(parenthesized(all.into_iter().collect(), span));
+            any.push(punct(',', span));
+        }
+        let any = [ident("any", span), parenthesized(any.into_iter().collect(), span)];
+        let cfg = [ident("cfg", span), parenthesized(any.into_iter().collect(), span)];
+        Some(Attribute::new(cfg.into_iter().collect(), span))
+    }
+
+    /// Replace `<Type as Trait>::Name` in the where clause with the default of `Name`,
+    /// rustc cannot normalize the impl's own associated types in its where clause.
+    pub fn resolve_own_types(&mut self, trait_ident: &Ident, defaults: &[(String, &[TokenTree])]) {
+        let trait_ident = trait_ident.to_string();
+        let tokens = std::mem::take(&mut self.where_clause);
+        let mut i = 0;
+        while i < tokens.len() {
+            if tokens[i].is('<') {
+                let mut depth = AngleDepth::default();
+                let end = (i..tokens.len()).find(|&j| {
+                    depth.push(&tokens[j]);
+                    depth.is_top()
+                });
+                if let Some(end) = end
+                    && let Some(as_token) = tokens[i..end].iter().position(|tt| tt.is_ident("as"))
+                    && last_segment(&tokens[i + as_token + 1..end])
+                        .first()
+                        .is_some_and(|tt| tt.is_ident(&trait_ident))
+                    && let [a, b, TokenTree::Ident(name), ..] = &tokens[end + 1..]
+                    && a.is(':')
+                    && b.is(':')
+                    && let Some((_, default)) = defaults.iter().find(|(n, _)| *n == name.to_string())
+                {
+                    self.where_clause.extend(default.iter().cloned());
+                    i = end + 4;
+                    continue;
+                }
+            }
+            self.where_clause.push(tokens[i].clone());
+            i += 1;
+        }
+    }
+
+    /// The same header implementing the trait for `&'__r Type`, or `&'__r mut Type` if `mutable`.
+    ///
+    /// The lifetime is named so predicates on the self type can be added to the where clause.
+    pub fn for_reference(&self, mutable: bool) -> Self {
+        let span = self.for_token.span();
+        let lifetime = lifetime("__r", span);
+        let mut generics = vec![punct('<', span)];
+        generics.extend(lifetime.iter().cloned());
+        match self.generics.as_slice() {
+            [_, params @ ..] if !params.is_empty() => {
+                generics.push(punct(',', span));
+                generics.extend(params.iter().cloned());
+            }
+            _ => generics.push(punct('>', span)),
+        }
+        let mut self_ty = vec![punct('&', span)];
+        self_ty.extend(lifetime);
+        if mutable {
+            self_ty.push(ident("mut", span));
+        }
+        self_ty.extend(self.self_ty.iter().cloned());
+        // The lifetime is only needed if a predicate names the self type.
+        let mut attrs = self.attrs.clone();
+        let mut lints = Group::new(Delimiter::Parenthesis, "clippy::needless_lifetimes".parse().expect("valid path"));
+        lints.set_span(span);
+        attrs.push(Attribute::allow(&lints));
+        ImplHeader {
+            attrs,
+            generics,
+            self_ty,
+            ..self.clone()
+        }
+    }
+
+    /// The same header implementing the trait for a smart pointer `Ptr<Type>`, e.g. `::std::rc::Rc`.
+    pub fn for_smart_ptr(&self, ptr: &[TokenTree]) -> Self {
+        let span = self.for_token.span();
+        let mut self_ty = ptr.to_vec();
+        self_ty.push(punct('<', span));
+        self_ty.extend(self.self_ty.iter().cloned());
+        self_ty.push(punct('>', span));
+        ImplHeader {
+            self_ty,
+            ..self.clone()
+        }
+    }
+
+    /// Append a predicate to the where clause.
+    pub fn push_predicate(&mut self, predicate: impl IntoIterator<Item = TokenTree>) {
+        match self.where_clause.last() {
+            None => self.where_clause.push(ident("where", self.for_token.span())),
+            Some(tt) if tt.is(',') || tt.is_ident("where") => (),
+            Some(tt) => self.where_clause.push(punct(',', tt.span())),
+        }
+        self.where_clause.extend(predicate);
+    }
+
+    /// Tokens of the impl block up to its body, implementing `path` instead of the header's trait.
+    /// The impl is never `default`, since a `default impl` does not implement `path` by itself.
+    pub fn to_tokens_with_path(&self, path: &[TokenTree], tokens: &mut Vec<TokenTree>) {
+        attrs_to_tokens(&self.attrs, tokens);
+        self.impl_to_tokens(path, tokens);
+    }
+
+    fn impl_to_tokens(&self, path: &[TokenTree], tokens: &mut Vec<TokenTree>) {
+        tokens.extend(self.unsafety.clone());
+        tokens.push(self.impl_token.clone());
+        tokens.extend(self.generics.iter().cloned());
+        tokens.extend(path.iter().cloned());
+        tokens.push(self.for_token.clone());
+        tokens.extend(self.self_ty.iter().cloned());
+        tokens.extend(self.where_clause.iter().cloned());
+    }
+
+    /// Tokens of the impl block up to its body.
+    pub fn to_tokens(&self, tokens: &mut Vec<TokenTree>) {
+        attrs_to_tokens(&self.attrs, tokens);
+        tokens.extend(self.defaultness.clone());
+        self.impl_to_tokens(&self.path, tokens);
+    }
+
+    /// The last segment of the header's trait path.
+    pub fn last_segment(&self) -> &[TokenTree] {
+        last_segment(&self.path)
+    }
+
+    /// The last segment of the header's trait path, renamed to `ident`.
+    pub fn last_segment_renamed(&self, ident: Ident) -> Vec<TokenTree> {
+        let mut path = vec![TokenTree::Ident(ident)];
+        path.extend(self.last_segment().iter().skip(1).cloned());
+        path
+    }
+}
+
+/// The last segment of a path, e.g. `B<T>` in `crate::traits::B<T>`.
+fn last_segment(path: &[TokenTree]) -> &[TokenTree] {
+    let mut depth = AngleDepth::default();
+    let mut last_segment = 0;
+    for (i, tt) in path.iter().enumerate() {
+        if depth.is_top() && tt.is(':') {
+            last_segment = i + 1;
+        }
+        depth.push(tt);
+    }
+    &path[last_segment..]
+}
+
+/// Identifiers in the types of associated type bindings `Name = Type`.
+fn binding_idents(tokens: &[TokenTree]) -> Vec<String> {
+    let mut names = Vec::new();
+    // Nesting of `<...>` inside the type of the binding being read.
+    let mut binding: Option<usize> = None;
+    for tt in tokens {
+        match &mut binding {
+            Some(0) if tt.is(',') || tt.is('>') => binding = None,
+            Some(depth) => {
+                if tt.is('<') {
+                    *depth += 1;
+                } else if tt.is('>') {
+                    *depth -= 1;
+                }
+                names.extend(idents(std::slice::from_ref(tt)));
+            }
+            None if tt.is('=') => binding = Some(0),
+            None => (),
+        }
+    }
+    names
+}
@@ -0,0 +1,609 @@
+use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use crate::attr::{Attribute, attrs_to_tokens, parse_outer};
+use crate::error::{Error, Result};
+use crate::tokens::{AngleDepth, TokenTreeExt, braced, ident, parenthesized, path_sep, punct, semi, split_commas};
+
+/// The trait the attribute is placed on.
+pub(crate) struct ItemTrait {
+    pub attrs: Vec<Attribute>,
+    pub vis: Vec<TokenTree>,
+    /// `unsafe` and `auto` before `trait`.
+    pub qualifiers: Vec<TokenTree>,
+    pub trait_token: TokenTree,
+    pub ident: Ident,
+    /// `<...>`, empty if the trait is not generic.
+    pub generics: Vec<TokenTree>,
+    /// `: A + B`, empty if there are no supertraits.
+    pub supertraits: Vec<TokenTree>,
+    /// `where ...`, empty if there is no where clause.
+    pub where_clause: Vec<TokenTree>,
+    pub brace_span: Span,
+    pub items: Vec<TraitItem>,
+}
+
+impl ItemTrait {
+    pub fn parse(tokens: TokenStream) -> Result<Self> {
+        let mut iter = tokens.into_iter().peekable();
+        let attrs = parse_outer(&mut iter)?;
+        let mut vis = Vec::new();
+        let mut qualifiers = Vec::new();
+        let trait_token = loop {
+            match iter.next() {
+                Some(tt) if tt.is_ident("trait") => break tt,
+                Some(tt) if tt.is_ident("unsafe") || tt.is_ident("auto") || !qualifiers.is_empty() => {
+                    qualifiers.push(tt)
+                }
+                Some(tt) => vis.push(tt),
+                None => return Err(Error::new(Span::call_site(), "expected a trait")),
+            }
+        };
+        let ident = match iter.next() {
+            Some(TokenTree::Ident(ident)) => ident,
+            Some(tt) => return Err(Error::new(tt.span(), "expected the trait name")),
+            None => return Err(Error::new(trait_token.span(), "expected the trait name")),
+        };
+        let mut generics = Vec::new();
+        if iter.peek().is_some_and(|tt| tt.is('<')) {
+            let mut depth = AngleDepth::default();
+            for tt in iter.by_ref() {
+                depth.push(&tt);
+                generics.push(tt);
+                if depth.is_top() {
+                    break;
+                }
+            }
+        }
+        let mut supertraits = Vec::new();
+        let mut where_clause = Vec::new();
+        let mut depth = AngleDepth::default();
+        let body = loop {
+            match iter.next() {
+                Some(TokenTree::Group(g)) if depth.is_top() && g.delimiter() == Delimiter::Brace => {
+                    break g;
+                }
+                Some(tt) => {
+                    depth.push(&tt);
+                    if (depth.is_top() && tt.is_ident("where")) || !where_clause.is_empty() {
+                        where_clause.push(tt);
+                    } else {
+                        supertraits.push(tt);
+                    }
+                }
+                None => return Err(Error::new(ident.span(), "expected the trait body")),
+            }
+        };
+        if let Some(tt) = iter.next() {
+            return Err(Error::new(tt.span(), "unexpected tokens after the trait body"));
+        }
+        Ok(ItemTrait {
+            attrs,
+            vis,
+            qualifiers,
+            trait_token,
+            ident,
+            generics,
+            supertraits,
+            where_clause,
+            brace_span: body.span(),
+            items: TraitItem::parse_all(body.stream())?,
+        })
+    }
+
+    /// Append a predicate to the where clause.
+    pub fn push_predicate(&mut self, predicate: impl IntoIterator<Item = TokenTree>) {
+        match self.where_clause.last() {
+            None => self.where_clause.push(ident("where", self.brace_span)),
+            Some(tt) if tt.is(',') || tt.is_ident("where") => (),
+            Some(tt) => self.where_clause.push(punct(',', tt.span())),
+        }
+        self.where_clause.extend(predicate);
+    }
+
+    /// Tokens of the trait declaration up to its body.
+    pub fn head_to_tokens(&self, tokens: &mut Vec<TokenTree>) {
+        attrs_to_tokens(&self.attrs, tokens);
+        tokens.extend(self.vis.iter().cloned());
+        tokens.extend(self.qualifiers.iter().cloned());
+        tokens.push(self.trait_token.clone());
+        tokens.push(TokenTree::Ident(self.ident.clone()));
+        tokens.extend(self.generics.iter().cloned());
+        tokens.extend(self.supertraits.iter().cloned());
+        tokens.extend(self.where_clause.iter().cloned());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ItemKind {
+    Fn,
+    Const,
+    Type,
+    Macro,
+    /// Tokens that are not recognized as an item.
+    Verbatim,
+}
+
+impl ItemKind {
+    /// Items with a signature and a default, macro invocations and verbatim tokens are copied as is.
+    ///
+    /// Every kind is listed, so a new kind has to decide how it is generated.
+    pub fn has_default(self) -> bool {
+        match self {
+            ItemKind::Fn | ItemKind::Const | ItemKind::Type => true,
+            ItemKind::Macro | ItemKind::Verbatim => false,
+        }
+    }
+
+    /// The error for an item without a default, `None` for kinds that never have one.
+    pub fn missing_default(self) -> Option<&'static str> {
+        match self {
+            ItemKind::Fn => Some("expected function body"),
+            ItemKind::Const | ItemKind::Type => Some("expected default value"),
+            ItemKind::Macro | ItemKind::Verbatim => None,
+        }
+    }
+
+    /// Whether the item is copied to the impl, verbatim tokens are left to the compiler in the trait.
+    pub fn in_impl(self) -> bool {
+        match self {
+            ItemKind::Fn | ItemKind::Const | ItemKind::Type | ItemKind::Macro => true,
+            ItemKind::Verbatim => false,
+        }
+    }
+}
+
+/// An item in the trait body.
+pub(crate) struct TraitItem {
+    pub attrs: Vec<Attribute>,
+    /// Attributes only present on the impl's copy of the item.
+    pub impl_attrs: Vec<Attribute>,
+    /// Attributes only present on the trait's declaration of the item.
+    pub trait_attrs: Vec<Attribute>,
+    pub kind: ItemKind,
+    /// Name of the item, `None` for macros and verbatim tokens.
+    pub ident: Option<Ident>,
+    /// Tokens of the item without attributes and default.
+    pub sig: Vec<TokenTree>,
+    /// The function body or `= value` of a constant or type.
+    pub default: Option<Vec<TokenTree>>,
+    pub semi: Option<TokenTree>,
+}
+
+impl TraitItem {
+    fn parse_all(stream: TokenStream) -> Result<Vec<Self>> {
+        let mut iter = stream.into_iter().peekable();
+        let mut items = Vec::new();
+        loop {
+            let attrs = parse_outer(&mut iter)?;
+            let Some(first) = iter.next() else {
+                if let Some(attr) = attrs.first() {
+                    return Err(Error::new(attr.span(), "expected an item after attributes"));
+                }
+                return Ok(items);
+            };
+            let mut item = TraitItem {
+                attrs,
+                impl_attrs: Vec::new(),
+                trait_attrs: Vec::new(),
+                kind: ItemKind::Verbatim,
+                ident: None,
+                sig: Vec::new(),
+                default: None,
+                semi: None,
+            };
+            let mut kind = None;
+            let mut depth = AngleDepth::default();
+            let mut tt = Some(first);
+            while let Some(current) = tt.take() {
+                if current.is(';') {
+                    item.semi = Some(current);
+                    break;
+                }
+                match kind {
+                    None => {
+                        if current.is_ident("fn") {
+                            kind = Some(ItemKind::Fn);
+                        } else if current.is_ident("type") {
+                            kind = Some(ItemKind::Type);
+                        } else if current.is_ident("const")
+                            && iter.peek().is_some_and(|tt| {
+                                matches!(tt, TokenTree::Ident(_))
+                                    && !["fn", "unsafe", "async", "extern"].iter().any(|kw| tt.is_ident(kw))
+                            })
+                        {
+                            kind = Some(ItemKind::Const);
+                        } else if current.is('!') {
+                            item.sig.push(current);
+                            match iter.next() {
+                                Some(group @ TokenTree::Group(_)) => {
+                                    let braced = group.is_brace();
+                                    item.sig.push(group);
+                                    if !braced {
+                                        item.semi = iter.next_if(|tt| tt.is(';'));
+                                    }
+                                }
+                                Some(tt) => return Err(Error::new(tt.span(), "expected a macro invocation")),
+                                None => return Err(Error::new(Span::call_site(), "expected a macro invocation")),
+                            }
+                            kind = Some(ItemKind::Macro);
+                            break;
+                        }
+                    }
+                    Some(k) => {
+                        if item.ident.is_none()
+                            && let TokenTree::Ident(ident) = &current
+                        {
+                            item.ident = Some(ident.clone());
+                        } else if k == ItemKind::Fn && depth.is_top() && current.is_brace() {
+                            item.default = Some(vec![current]);
+                            break;
+                        } else if k != ItemKind::Fn && depth.is_top() && current.is('=') {
+                            let mut default = vec![current];
+                            item.semi = loop {
+                                match iter.next() {
+                                    Some(tt) if tt.is(';') => break Some(tt),
+                                    Some(tt) => default.push(tt),
+                                    None => break None,
+                                }
+                            };
+                            item.default = Some(default);
+                            break;
+                        }
+                    }
+                }
+                depth.push(&current);
+                item.sig.push(current);
+                tt = iter.next();
+            }
+            item.kind = kind.unwrap_or(ItemKind::Verbatim);
+            if item.kind == ItemKind::Type
+                && let Some(default) = &mut item.default
+            {
+                // `type X<'a> = Y where Self: 'a;` has the where clause after the value,
+                // keep it with the signature for the declaration.
+                let mut depth = AngleDepth::default();
+                if let Some(i) = default.iter().position(|tt| {
+                    depth.push(tt);
+                    depth.is_top() && tt.is_ident("where")
+                }) {
+                    item.sig.extend(default.split_off(i));
+                }
+            }
+            item.route_attrs();
+            items.push(item);
+        }
+    }
+
+    /// Move attributes that only apply to the trait or the impl out of the shared attributes,
+    /// e.g. `#[must_use]` to the trait and `#[inline]` or `#[expect(...)]` to the impl.
+    pub fn route_attrs(&mut self) {
+        if !self.kind.has_default() {
+            return;
+        }
+        for attr in std::mem::take(&mut self.attrs) {
+            if attr.is_expect() || (self.kind == ItemKind::Fn && attr.is_codegen()) {
+                self.impl_attrs.push(attr);
+            } else if attr.is_must_use() || attr.is_deprecated() {
+                self.trait_attrs.push(attr);
+            } else {
+                self.attrs.push(attr);
+            }
+        }
+    }
+
+    /// Span used to report errors on this item.
+    pub fn span(&self) -> Span {
+        match &self.ident {
+            Some(ident) => ident.span(),
+            None => self.sig.first().map_or(Span::call_site(), TokenTree::span),
+        }
+    }
+
+    /// Generic parameters of a function, constant or type, `<` and `>` excluded.
+    pub fn generic_params(&self) -> Vec<Vec<TokenTree>> {
+        let Some(ident) = &self.ident else {
+            return Vec::new();
+        };
+        let ident = ident.to_string();
+        let mut iter = self.sig.iter().skip_while(|tt| !tt.is_ident(&ident)).skip(1);
+        if !iter.next().is_some_and(|tt| tt.is('<')) {
+            return Vec::new();
+        }
+        let mut depth = AngleDepth::default();
+        depth.push(&TokenTree::Punct(Punct::new('<', Spacing::Alone)));
+        let params = iter
+            .take_while(|tt| {
+                depth.push(tt);
+                !depth.is_top()
+            })
+            .cloned();
+        split_commas(params.collect())
+    }
+
+    /// Parameters of a function.
+    pub fn fn_inputs(&self) -> Option<Group> {
+        match &self.sig[inputs_index(&self.sig)?] {
+            TokenTree::Group(g) => Some(g.clone()),
+            _ => None,
+        }
+    }
+
+    /// The receiver of a function, e.g. `&self`, `None` for associated functions.
+    pub fn receiver(&self) -> Option<Vec<TokenTree>> {
+        let first = split_commas(self.fn_inputs()?.stream()).into_iter().next()?;
+        let mut depth = AngleDepth::default();
+        let is_receiver = first
+            .iter()
+            .take_while(|tt| {
+                depth.push(tt);
+                !(depth.is_top() && tt.is(':'))
+            })
+            .any(|tt| tt.is_ident("self"));
+        is_receiver.then_some(first)
+    }
+
+    /// Check if the receiver is `&self`, `&'a self`, `self: &Self` or `self: &'a Self`.
+    pub fn has_ref_receiver(&self) -> bool {
+        let Some(receiver) = self.receiver() else {
+            return false;
+        };
+        let (last, reference) = match receiver.as_slice() {
+            [self_token, colon, rest @ ..] if self_token.is_ident("self") && colon.is(':') => ("Self", rest),
+            rest => ("self", rest),
+        };
+        match reference {
+            [and, receiver] => and.is('&') && receiver.is_ident(last),
+            [and, quote, _, receiver] => and.is('&') && quote.is('\'') && receiver.is_ident(last),
+            _ => false,
+        }
+    }
+
+    /// Find a `self.name()` call to this method in its own default, only for methods
+    /// without parameters besides `self`, where such a call can only recurse forever.
+    pub fn find_self_call(&self) -> Option<Span> {
+        fn find(tokens: &[TokenTree], name: &str) -> Option<Span> {
+            tokens.iter().enumerate().find_map(|(i, tt)| match tt {
+                TokenTree::Group(g) => find(&g.stream().into_iter().collect::<Vec<_>>(), name),
+                _ => match &tokens[i..] {
+                    [receiver, dot, method, TokenTree::Group(args), ..]
+                        if receiver.is_ident("self")
+                            && dot.is('.')
+                            && method.is_ident(name)
+                            && args.delimiter() == Delimiter::Parenthesis
+                            && args.stream().is_empty()
+                            // Not a field access or a method call on `self.field`.
+                            && !(i > 0 && tokens[i - 1].is('.')) =>
+                    {
+                        Some(method.span())
+                    }
+                    _ => None,
+                },
+            })
+        }
+        let inputs = self.fn_inputs()?;
+        if self.kind != ItemKind::Fn || self.receiver().is_none() || split_commas(inputs.stream()).len() != 1 {
+            return None;
+        }
+        find(self.default.as_ref()?, &self.ident.as_ref()?.to_string())
+    }
+
+    /// Check if the signature contains `Self: Sized`.
+    fn requires_sized(&self) -> bool {
+        self.sig
+            .windows(3)
+            .any(|w| w[0].is_ident("Self") && w[1].is(':') && w[2].is_ident("Sized"))
+    }
+
+    /// Error if the item prevents the trait from being used as `dyn Trait`.
+    pub fn check_object_safe(&self) -> Result<()> {
+        let reason = match self.kind {
+            _ if self.requires_sized() => return Ok(()),
+            ItemKind::Const => "associated constants",
+            ItemKind::Type if !self.generic_params().is_empty() => "generic associated types",
+            ItemKind::Fn if self.receiver().is_none() => "functions without a `self` receiver",
+            ItemKind::Fn if self.generic_params().iter().any(|param| !param[0].is('\'')) => "generic methods",
+            ItemKind::Fn if self.sig.iter().any(|tt| tt.is_ident("async")) => "`async fn`",
+            ItemKind::Fn
+                if self
+                    .sig
+                    .windows(3)
+                    .any(|w| w[0].is('-') && w[1].is('>') && w[2].is_ident("impl")) =>
+            {
+                "functions returning `impl Trait`"
+            }
+            _ => return Ok(()),
+        };
+        Err(Error::new(
+            self.span(),
+            format!("the trait must be object safe, {reason} are not allowed without `where Self: Sized`"),
+        ))
+    }
+
+    /// The body `{ Self::base(self, args..) }` of a function delegating to `base`.
+    pub fn delegate(&self, base: &Ident) -> Result<TokenTree> {
+        let span = base.span();
+        let mut args = Vec::new();
+        let has_receiver = self.receiver().is_some();
+        let inputs = self
+            .fn_inputs()
+            .map(|inputs| split_commas(inputs.stream()))
+            .unwrap_or_default();
+        for (i, arg) in inputs.into_iter().enumerate() {
+            if i > 0 {
+                args.push(punct(',', span));
+            }
+            match arg.as_slice() {
+                _ if i == 0 && has_receiver => args.push(ident("self", span)),
+                [name @ TokenTree::Ident(_), colon, ..] if colon.is(':') => args.push(name.clone()),
+                _ => return Err(Error::new(arg[0].span(), "expected a parameter name")),
+            }
+        }
+        let mut body = vec![ident("Self", span)];
+        body.extend(path_sep(span));
+        body.push(TokenTree::Ident(base.clone()));
+        body.push(parenthesized(args.into_iter().collect(), span));
+        Ok(braced(body.into_iter().collect(), span))
+    }
+
+    /// The signature of the item as declared in the trait.
+    ///
+    /// A declaration cannot bind its parameters mutably or with patterns, so `mut self` becomes `self`,
+    /// `mut x: T` becomes `x: T` and other patterns become `_`.
+    pub fn declared_sig(&self) -> Vec<TokenTree> {
+        match self.kind {
+            ItemKind::Fn => declared_params(&self.sig).unwrap_or_else(|| self.sig.clone()),
+            _ => self.sig.clone(),
+        }
+    }
+
+    /// Error if the signature declared in the trait differs from the one defined in the impl,
+    /// other than `mut` bindings and patterns of parameters, which are only kept in the impl.
+    pub fn check_sig_matches(&self) -> Result<()> {
+        let declared: TokenStream = self.declared_sig().into_iter().collect();
+        let (mut defined, where_clause) = Self::defined_sig(self.kind, self.sig.clone());
+        defined.extend(where_clause);
+        let defined: TokenStream = match self.kind {
+            ItemKind::Fn => declared_params(&defined).unwrap_or(defined),
+            _ => defined,
+        }
+        .into_iter()
+        .collect();
+        if declared.to_string() != defined.to_string() {
+            return Err(Error::new(
+                self.span(),
+                format!("the trait declares `{declared}` but the impl defines `{defined}`"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The item as declared in the trait.
+    pub fn declaration_to_tokens(&self, tokens: &mut Vec<TokenTree>) {
+        if !self.kind.has_default() {
+            attrs_to_tokens(&self.attrs, tokens);
+            tokens.extend(self.sig.iter().cloned());
+            tokens.extend(self.semi.clone());
+            return;
+        }
+        attrs_to_tokens(&self.attrs, tokens);
+        attrs_to_tokens(&self.trait_attrs, tokens);
+        tokens.extend(self.declared_sig());
+        match (&self.semi, &self.default) {
+            (Some(semi), _) => tokens.push(semi.clone()),
+            (None, Some(default)) => tokens.push(semi(default[0].span())),
+            (None, None) => (),
+        }
+    }
+
+    /// The item as an inherent item with visibility `vis`.
+    pub fn inherent_to_tokens(&self, vis: &[TokenTree], tokens: &mut Vec<TokenTree>) {
+        attrs_to_tokens(&self.attrs, tokens);
+        attrs_to_tokens(&self.trait_attrs, tokens);
+        attrs_to_tokens(&self.impl_attrs, tokens);
+        tokens.extend(vis.iter().cloned());
+        tokens.extend(self.sig.iter().cloned());
+        tokens.extend(self.default.iter().flatten().cloned());
+        tokens.extend(self.semi.clone());
+    }
+
+    /// The signature of the item as defined in the impl and its where clause, the impl cannot
+    /// repeat the bounds of an associated type, e.g. `type Item: Clone` becomes `type Item`.
+    fn defined_sig(kind: ItemKind, tokens: Vec<TokenTree>) -> (Vec<TokenTree>, Vec<TokenTree>) {
+        if kind != ItemKind::Type {
+            return (tokens, Vec::new());
+        }
+        let mut depth = AngleDepth::default();
+        let mut in_bounds = false;
+        let mut sig = Vec::new();
+        let mut where_clause = Vec::new();
+        for tt in tokens {
+            depth.push(&tt);
+            if depth.is_top() && tt.is(':') && where_clause.is_empty() {
+                in_bounds = true;
+            } else if (depth.is_top() && tt.is_ident("where")) || !where_clause.is_empty() {
+                where_clause.push(tt);
+            } else if !in_bounds {
+                sig.push(tt);
+            }
+        }
+        (sig, where_clause)
+    }
+
+    /// The item as defined in the impl, verbatim tokens only appear in the trait.
+    ///
+    /// This is the last use of the item, so its tokens are moved instead of cloned.
+    pub fn into_definition_tokens(self, tokens: &mut Vec<TokenTree>) {
+        if !self.kind.in_impl() {
+            return;
+        }
+        attrs_to_tokens(&self.attrs, tokens);
+        attrs_to_tokens(&self.impl_attrs, tokens);
+        let (sig, where_clause) = Self::defined_sig(self.kind, self.sig);
+        tokens.extend(sig);
+        // The where clause of a type goes after its value.
+        tokens.extend(self.default.into_iter().flatten());
+        tokens.extend(where_clause);
+        tokens.extend(self.semi);
+    }
+}
+
+/// Index of the parameters in a function signature, skipping groups in the generics, e.g. `<F: Fn(i32)>`.
+fn inputs_index(sig: &[TokenTree]) -> Option<usize> {
+    let mut depth = AngleDepth::default();
+    sig.iter().position(|tt| {
+        depth.push(tt);
+        depth.is_top() && matches!(tt, TokenTree::Group(g) if g.delimiter() == Delimiter::Parenthesis)
+    })
+}
+
+/// The signature of a function with its parameters as declared in a trait, see [`TraitItem::declared_sig`],
+/// `None` if no parameter changes.
+fn declared_params(sig: &[TokenTree]) -> Option<Vec<TokenTree>> {
+    let index = inputs_index(sig)?;
+    let TokenTree::Group(inputs) = &sig[index] else {
+        return None;
+    };
+    let mut args = Vec::new();
+    let mut stripped = false;
+    for (i, arg) in split_commas(inputs.stream()).into_iter().enumerate() {
+        if i > 0 {
+            args.push(punct(',', inputs.span()));
+        }
+        match arg.as_slice() {
+            [mutability, binding @ TokenTree::Ident(_), rest @ ..]
+                if mutability.is_ident("mut")
+                    && (binding.is_ident("self") || rest.first().is_some_and(|tt| tt.is(':'))) =>
+            {
+                stripped = true;
+                args.push(binding.clone());
+                args.extend(rest.iter().cloned());
+            }
+            _ => match pattern_len(&arg) {
+                // A declaration cannot destructure, e.g. `(a, b): (i32, i32)` becomes `_: (i32, i32)`.
+                Some(len) if len > 1 || !matches!(arg[0], TokenTree::Ident(_)) => {
+                    stripped = true;
+                    args.push(ident("_", arg[0].span()));
+                    args.extend(arg[len..].iter().cloned());
+                }
+                _ => args.extend(arg),
+            },
+        }
+    }
+    // Keep the original tokens, and their formatting in messages, if nothing is stripped.
+    if !stripped {
+        return None;
+    }
+    let mut group = Group::new(Delimiter::Parenthesis, args.into_iter().collect());
+    group.set_span(inputs.span());
+    let mut sig = sig.to_vec();
+    sig[index] = TokenTree::Group(group);
+    Some(sig)
+}
+
+/// Number of tokens in the pattern of a typed function parameter, `None` if there is no type, e.g. `&self`.
+fn pattern_len(arg: &[TokenTree]) -> Option<usize> {
+    (0..arg.len()).find(|&i| {
+        let is_path = |tt: Option<&TokenTree>| tt.is_some_and(|tt| tt.is(':'));
+        arg[i].is(':') && !is_path(arg.get(i + 1)) && !(i > 0 && is_path(arg.get(i - 1)))
+    })
+}
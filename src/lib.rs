@@ -1,67 +1,265 @@
 #![doc = include_str!("../README.md")]
-use proc_macro::{Delimiter, Group, Punct, Spacing, Span, TokenStream, TokenTree};
+use std::collections::HashSet;
 
-trait TokenTreeExt {
-    fn is_brace(&self) -> bool;
-    fn braced_group(&self) -> Option<&Group>;
-    fn is(&self, c: char) -> bool;
-    fn is_new_item_trait(&self) -> bool;
-}
-
-impl TokenTreeExt for TokenTree {
-    fn is_brace(&self) -> bool {
-        if let TokenTree::Group(g) = self && g.delimiter() == Delimiter::Brace {
-            true
-        } else {
-            false
-        }
-    }
-
-    fn braced_group(&self) -> Option<&Group> {
-        if let TokenTree::Group(g) = self && g.delimiter() == Delimiter::Brace {
-            Some(g)
-        } else {
-            None
-        }
-    }
-
-    fn is(&self, c: char) -> bool {
-        if let TokenTree::Punct(p) = self && p.as_char() == c {
-            true
-        } else {
-            false
-        }
-    }
-
-    fn is_new_item_trait(&self) -> bool {
-        if let TokenTree::Ident(p) = self  {
-            let p = p.to_string();
-            p == "fn" || p == "const" || p == "type"
-        } else {
-            false
-        }
-    }
-}
+use proc_macro::{Delimiter, Ident, Span, TokenStream, TokenTree};
 
-fn semi(span: Span) -> TokenTree {
-    let mut semi = TokenTree::Punct(Punct::new(';', Spacing::Alone));
-    semi.set_span(span);
-    semi
-}
+mod attr;
+mod boxed;
+mod debug;
+mod error;
+mod header;
+mod item;
+mod module;
+mod options;
+mod seal;
+mod tokens;
 
-fn braced(tokens: TokenStream, span: Span) -> TokenTree {
-    let mut braced = TokenTree::Group(Group::new(Delimiter::Brace, tokens));
-    braced.set_span(span);
-    braced
-}
+use attr::{Attribute, attrs_to_tokens, parse_outer, take_directives};
+use error::{Error, Result};
+use header::ImplHeader;
+use item::{ItemKind, ItemTrait, TraitItem};
+use options::{ItemOptions, TraitOptions};
+use tokens::{
+    TokenTreeExt, braced, capture_params, find_path, find_path_by, generic_name, generic_names, ident, idents, join_bounds,
+    param_names, parenthesized, path_sep, punct, split_commas, str_value,
+};
 
 /// Generate a trait with a blanket implementation.
 ///
 /// # Rules
 ///
 /// * Generated `trait` block will not contain any default implementations.
-/// * Errors if any item do not contain a default implementation.
-/// * Attributes on fields are copied to both instances.
+/// * Errors if any item do not contain a default implementation, unless the trait is `partial`.
+/// * Attributes on fields are copied to both instances, except `#[inline]`, `#[cold]`, `#[track_caller]`
+///   and `#[no_mangle]` on functions, which only apply to the impl.
+///   `#[must_use]` and `#[deprecated]` only apply to the trait, and `#[expect(...)]` on any item only applies
+///   to the impl which contains the default, use `#[allow(...)]` for lints on the declaration.
+/// * Generated impls are marked `#[automatically_derived]`.
+/// * Attributes before `impl` in the header, e.g. `#[automatically_derived] impl<T: A> B for T`,
+///   are only applied to that impl.
+/// * Attributes on the trait stay on the trait, except `#[cfg]` and `#[cfg_attr]`
+///   which are also applied to the impls.
+/// * Supertraits of the trait are added to the where clause of the impl.
+/// * Default implementations are only present in the impl, where `Self` is the impl's self type.
+///   For `impl<T: A> B for T`, `type X = Self;` and `type X = T;` are the same.
+///   `Self::name` is ambiguous if both the trait and a bound trait define `name`, use `<Self as A>::name`.
+/// * Default bodies are copied verbatim, including local items. Like in any impl, a local `fn` or `struct`
+///   cannot use the impl's type parameters, declare its own instead, e.g. `fn helper<U: A>(value: &U)`.
+/// * `Self` in precise capturing lists, e.g. `impl Iterator + use<'_, Self>`, is replaced with
+///   the type parameters of the impl, since impls cannot capture `Self`.
+/// * Parameters bound with `mut`, e.g. `mut self` or `mut x: i32`, are declared without `mut` in the trait
+///   and keep it in the impl.
+///   Patterns, e.g. `(a, b): (i32, i32)`, are declared as `_` in the trait.
+/// * Bounds of associated types, e.g. `type X: Clone = T::AA;`, are only present in the trait.
+///   Where clauses of generic associated types are present in both, after the value in the impl.
+/// * Items gated with `#[cfg]` are gated in both instances, a missing default is only an error
+///   if the item is compiled.
+/// * Macro invocations are copied verbatim to both instances,
+///   so items they expand to keep their default implementations in the trait.
+/// * Type parameters of the impl must be used by the self type or the implemented trait,
+///   e.g. `impl<T: A> B for u32` is an error.
+/// * Generic parameters of the trait used in the impl header must be declared in the impl generics,
+///   e.g. `impl<Out, T: A> Convert<Out> for T`.
+/// * `<T as Trait>::X` in the impl header's where clause is replaced with the default of `X`,
+///   e.g. `where <T as D>::X: Clone` becomes `where T::AA: Clone` for `type X = T::AA;`.
+/// * `Self: Bounds` predicates in the impl header's where clause are also added to the where clause
+///   of the trait, unless they use the impl's type parameters. Other predicates only apply to the impl.
+/// * A default calling `self.method()` on itself recurses unless a bound trait of the impl supplies
+///   `method`, rustc warns about it and `#[blanket(deny_self_recursion)]` makes it an error.
+///   Use `T::method(self)` or `<T as Trait>::method(self)` to call the bound trait explicitly.
+/// * Paths like `T::method` in default implementations require `T` to have bounds in the impl header.
+///   Whether the bounds provide `method` is not known to the macro and is left to the compiler.
+/// * Paths like `T::method` in default implementations must use a type parameter that every impl declares.
+///   Names are checked if an impl or the trait declares them, or if they are a single uppercase letter
+///   followed by a lowercase item, e.g. `T::a()` with `impl B for Config`, unless the headers or supertraits
+///   name them, like a trait `A`.
+/// * Generic parameters of an item with a default cannot reuse the name of a type parameter of the impl.
+/// * `const fn` is an error, as traits cannot declare `const fn` on stable Rust.
+/// * A function defined more than once, each definition with a default and a `#[cfg]`, is declared once
+///   in the trait without the `#[cfg]`, and every definition is copied to the impl with its `#[cfg]`.
+/// * Unrecognized tokens in the trait body are copied verbatim to the trait only,
+///   leaving the compiler to report them.
+///
+/// # Multiple Implementations
+///
+/// The attribute accepts a comma separated list of impl headers,
+/// the trait is generated once and the default implementations are copied to each impl.
+///
+/// ```
+/// # use blanket_trait::blanket_trait;
+/// trait Name {
+///     fn name(&self) -> &str;
+/// }
+///
+/// #[blanket_trait(impl<T: Name> Greet for T, impl<T: Name> Greet for [T] where T: Clone)]
+/// pub trait Greet {
+///     fn greet(&self) -> String {
+///         String::from("hello")
+///     }
+/// }
+/// ```
+///
+/// A header can also list several self types, e.g. concrete types without a shared trait,
+/// `impl Trait for A, B, C` is the same as `impl Trait for A, impl Trait for B, impl Trait for C`.
+/// The where clause, if any, applies to each impl.
+///
+/// ```
+/// # use blanket_trait::blanket_trait;
+/// pub struct Cat;
+/// pub struct Dog;
+///
+/// impl Cat { fn name(&self) -> &str { "cat" } }
+/// impl Dog { fn name(&self) -> &str { "dog" } }
+///
+/// #[blanket_trait(impl Greet for Cat, Dog)]
+/// pub trait Greet {
+///     fn greet(&self) -> String {
+///         format!("hello {}", self.name())
+///     }
+/// }
+///
+/// assert_eq!(Dog.greet(), "hello dog");
+/// ```
+///
+/// # Wrappers
+///
+/// The self type can be any type using the impl's parameters, e.g. a newtype.
+/// The default implementations are written for the wrapper and delegate to the field themselves.
+///
+/// ```
+/// # use blanket_trait::blanket_trait;
+/// # trait Name { fn name(&self) -> &str; }
+/// pub struct Loud<T>(T);
+///
+/// #[blanket_trait(impl<T: Name> Greet for Loud<T>)]
+/// pub trait Greet {
+///     fn greet(&self) -> String {
+///         format!("HELLO {}", self.0.name().to_uppercase())
+///     }
+/// }
+/// ```
+///
+/// # Trait Attributes
+///
+/// The trait can be annotated with `#[blanket(...)]` after the `blanket_trait` attribute:
+///
+/// * `#[blanket(allow_mismatch)]`: allow the impl headers to implement a trait
+///   with a different name than the one being defined.
+/// * `#[blanket(allow_empty)]`: allow a trait without items, e.g. a marker trait.
+/// * `#[blanket(allow_unused_generic)]`: allow impl type parameters that appear neither in the trait,
+///   the self type nor an associated type binding like `Iterator<Item = U>`, e.g. `U` in `F: Fn() -> U`.
+/// * `#[blanket(partial)]`: keep items without a default implementation as required items of the trait
+///   and leave them out of the impl. The impl only compiles if the implemented trait
+///   provides these items, for example an existing trait targeted with `allow_mismatch`.
+/// * `#[blanket(map(name = base_name))]`: give the function `name` without a default the default
+///   `Self::base_name(self, args..)`, delegating to a method of a bound trait with another name.
+/// * `#[blanket(only(a, b))]`: only implement the listed items in the impl and leave the others
+///   to the implementor, like the required items of a `partial` trait.
+/// * `#[blanket(cfg_trait)]`: apply the `#[cfg(...)]` attributes of the impl headers to the trait,
+///   so the trait and its impls are compiled together.
+///
+/// ```
+/// # use blanket_trait::blanket_trait;
+/// # trait Name { fn name(&self) -> &str; }
+/// #[blanket_trait(#[cfg(feature = "greet")] impl<T: Name> Greet for T)]
+/// #[blanket(cfg_trait)]
+/// pub trait Greet {
+///     fn greet(&self) -> String {
+///         format!("hello {}", self.name())
+///     }
+/// }
+/// ```
+///
+/// * `#[blanket(boxed)]` or `#[blanket(boxed = Name)]`: generate a `Box<dyn Trait>` wrapper
+///   with `From`, `Deref` and `DerefMut` implementations, named `Boxed{Trait}` by default.
+///   The associated types become type parameters of the wrapper, e.g. `BoxedTrait<Out>`.
+///   Errors if the trait is not object safe.
+///
+/// ```
+/// # use blanket_trait::blanket_trait;
+/// # trait Name { fn name(&self) -> &str; }
+/// #[blanket_trait(impl<T: Name> Greet for T)]
+/// #[blanket(boxed)]
+/// pub trait Greet {
+///     fn greet(&self) -> String {
+///         format!("hello {}", self.name())
+///     }
+/// }
+///
+/// # struct World;
+/// # impl Name for World { fn name(&self) -> &str { "world" } }
+/// let greet = BoxedGreet::from(World);
+/// assert_eq!(greet.greet(), "hello world");
+/// ```
+///
+/// * `#[blanket(into_box)]` or `#[blanket(into_box = name)]`: generate a function converting a value
+///   to `Box<dyn Trait>`, named `into_boxed_{trait}` by default. Errors if the trait is not object safe.
+/// * `#[blanket(object_safe)]`: assert that the trait can be used as `dyn Trait`.
+///   Errors on items that are not object safe, like `boxed`.
+/// * `#[blanket(assert_impl = Type)]`: assert that `Type` implements the trait, so an impl that stops applying
+///   to `Type` is reported at the attribute. Can be repeated.
+/// * `#[blanket(bound(Send, Clone))]`: add `T: Send + Clone` to the where clause of the impls,
+///   where `T` is the only type parameter of the impl.
+/// * `#[blanket(default_for = Type)]`: also add the functions and constants to `Type` as `pub` inherent items.
+///   Their defaults cannot use the type parameters of the impl.
+/// * `#[blanket(order = "impl_first")]`: emit the impls before the trait, the default is `"trait_first"`.
+/// * `#[blanket(impl_only_output)]`: only emit the impls, for a trait declared elsewhere with the same items.
+///   Not allowed with `sealed`.
+///   This also implements foreign traits, e.g. `impl<T: Name> std::fmt::Display for Named<T>`,
+///   the orphan rules still apply so the self type must be a local type, not a bare type parameter.
+/// * `#[blanket(crate_path = path::to::module)]`: name the trait as `path::to::module::Trait` in the impls,
+///   e.g. `crate_path = $crate::traits` in a `macro_rules!` or with `impl_only_output`.
+/// * `#[blanket(default_impl)]`: emit the impls as `default impl`, types then implement the trait with
+///   `impl Trait for Type {}` and can override its items. Requires nightly and `#![feature(specialization)]`.
+/// * `#[blanket(allow(clippy::type_complexity, dead_code))]`: add `#[allow(...)]` to the trait and the impls.
+/// * `#[blanket(doc_hidden)]`: add `#[doc(hidden)]` to the trait, its impls, re-exports and module.
+/// * `#[blanket(on_unimplemented = "...")]`: add `#[diagnostic::on_unimplemented(message = "...")]` to the trait.
+/// * `#[blanket(ref_impls)]`: also implement the trait for `&Type` and `&mut Type` of each impl,
+///   all methods must take `&self`. Not allowed for `impl<T> Trait for T`, which already covers `&T`.
+/// * `#[blanket(smart_ptr(Arc, Rc))]`: also implement the trait for `Arc<Type>` and `Rc<Type>` of each impl,
+///   or any other listed pointer type. All methods must take `&self`, the default implementations
+///   reach the value through auto-deref and deref coercion, e.g. `self.name()` or `T::name(self)`.
+/// * `#[blanket(where_self(Clone, Sized))]`: add `Self: Clone + Sized` to the where clause of the trait
+///   and the matching bounds to the impls.
+/// * `#[blanket(base = Name)]`: import the bound trait of the impl's type parameter as `Name` for
+///   the default implementations, e.g. `Name::method(self)` for `impl<T: path::to::LongName> Trait for T`.
+///   The impls are placed in a `const _: () = { ... };` block to scope the import.
+/// * `#[blanket(scoped)]`: place the impls and the helper trait of skipped items in a `const _: () = { ... };`
+///   block, so only the trait is added to the module. The `Sealed` trait of `sealed` is not scoped,
+///   since the trait names it as a supertrait.
+/// * `#[blanket(reexport)]` or `#[blanket(reexport = path::Trait)]`: re-export the bound trait
+///   with the visibility of the trait, inferred from the only bound of the impl's type parameter.
+/// * `#[blanket(module = name)]`: generate the items in `pub mod name` and re-export them with `use name::*`
+///   using the visibility of the trait. Visibilities relative to the current module, e.g. `pub(super)` or
+///   `pub(in super::path)`, are adjusted to mean the same inside the module.
+/// * `#[blanket(vis = "pub(crate)")]` or `#[blanket(vis = pub(in crate::path))]`: override the visibility
+///   of the trait, use `vis = ""` for a private trait.
+/// * `#[blanket(no_auto_derived)]`: do not add `#[automatically_derived]` to the generated impls.
+/// * `#[blanket(strict)]`: error if the signature of an item in the trait differs from the one in the impl,
+///   other than `mut` bindings and patterns of parameters. Bounds of associated types are a difference.
+/// * `#[blanket(deny_self_recursion)]`: deny `unconditional_recursion` on the impls, so a default calling
+///   itself is an error instead of a warning, e.g. `self.label()` in the default of `label` for
+///   `impl<T: Display> Label for T`. Calls to methods of bound traits are not affected, `self.name()` calls
+///   `A::name` in `impl<T: A> B for T`. Without bounds that could supply it, any `self.name()` call to a method
+///   `name` without other parameters is an error, even if it is conditional.
+///   Without the directive, rustc warns about defaults that always call themselves, since the impls keep the
+///   spans of the defaults.
+/// * `#[blanket(debug)]`: also write the formatted expansion to `target/blanket_trait/{Trait}.rs`.
+///   Requires the `debug-output` feature.
+/// * `#[blanket(sealed)]`: add a private `Sealed` supertrait only implemented by the impl headers,
+///   so other crates cannot implement the trait. Not allowed with `allow_mismatch`.
+///
+/// # Item Attributes
+///
+/// Items in the trait body can be annotated with `#[blanket(...)]`:
+///
+/// * `#[blanket(skip)]`: keep the item out of the generated trait.
+///   The item is implemented on a private helper trait with the same blanket implementation,
+///   so it can still be called from other default implementations. Errors if the item has no default.
+/// * `#[blanket(impl_only)]`: same as `skip`, but only allowed on functions.
+///   Use this for private helper functions that are never meant to be part of the trait.
+/// * `#[blanket(feature = "name")]`: add `#[cfg(feature = "name")]` to the item in the trait and the impl.
+/// * `#[blanket(doc_impl = "...")]`: add documentation to the impl's copy of the item only.
 ///
 /// # Syntax
 ///
@@ -104,6 +302,7 @@ fn braced(tokens: TokenStream, span: Span) -> TokenTree {
 ///     fn dyn_clone(&self) -> Box<dyn ErasedBehavior>;
 /// }
 ///
+/// #[automatically_derived]
 /// impl<T: Behavior> ErasedBehavior for T where T: Send + Sync + Clone + 'static {
 ///     fn name(&self) -> &str {
 ///         T::name()
@@ -121,45 +320,987 @@ fn braced(tokens: TokenStream, span: Span) -> TokenTree {
 ///
 #[proc_macro_attribute]
 pub fn blanket_trait(impl_header: TokenStream, tokens: TokenStream) -> TokenStream {
-    let mut impl_block: Vec<_> = impl_header.into_iter().collect();
-    let mut trait_block = Vec::new();
-    for tt in tokens {
-        let span = tt.span();
-        if let Some(g) = tt.braced_group() {
-            let mut impl_block_inner = Vec::new();
-            let mut trait_block_inner = Vec::new();
-            let mut is_default_member = false;
-            let mut angle_bracket_depth = 0;
-            for tt in g.stream() {
-                if tt.is_brace() {
-                    trait_block_inner.push(semi(tt.span()));
-                    impl_block_inner.push(tt);
-                } else if angle_bracket_depth <= 0 && tt.is('=')  {
-                    is_default_member = true;
-                    impl_block_inner.push(tt);
-                } else if is_default_member && (tt.is(';') || tt.is_new_item_trait()) {
-                    is_default_member = false;
-                    trait_block_inner.push(tt.clone());
-                    impl_block_inner.push(tt);
-                    angle_bracket_depth = 0;
-                } else if is_default_member {
-                    impl_block_inner.push(tt);
-                } else {
-                    if tt.is('<') {
-                        angle_bracket_depth += 1;
-                    // maybe inaccurate way to ignore the ->
-                    } else if tt.is('>') && angle_bracket_depth > 0 {
-                        angle_bracket_depth -= 1;
-                    }
-                    trait_block_inner.push(tt.clone());
-                    impl_block_inner.push(tt);
+    if impl_header.is_empty() {
+        return Error::new(
+            Span::call_site(),
+            "expected an impl header, e.g. `#[blanket_trait(impl<T: Base> Trait for T)]`",
+        )
+        .into_compile_error();
+    }
+    expand(impl_header, tokens).unwrap_or_else(Error::into_compile_error)
+}
+
+/// Function-like version of [`macro@blanket_trait`], for use inside other macros.
+///
+/// Takes the impl headers, a semicolon, then the trait.
+///
+/// ```
+/// # use blanket_trait::blanket_trait_impl;
+/// trait Name {
+///     fn name(&self) -> &str;
+/// }
+///
+/// macro_rules! greet {
+///     ($name: ident) => {
+///         blanket_trait_impl! {
+///             impl<T: Name> $name for T;
+///             pub trait $name {
+///                 fn greet(&self) -> String {
+///                     format!("hello {}", self.name())
+///                 }
+///             }
+///         }
+///     };
+/// }
+///
+/// greet!(Greet);
+/// ```
+#[proc_macro]
+pub fn blanket_trait_impl(input: TokenStream) -> TokenStream {
+    expand_fn(input).unwrap_or_else(Error::into_compile_error)
+}
+
+fn expand_fn(input: TokenStream) -> Result<TokenStream> {
+    let mut iter = input.into_iter();
+    let mut impl_header = Vec::new();
+    loop {
+        match iter.next() {
+            Some(tt) if tt.is(';') => break,
+            Some(tt) => impl_header.push(tt),
+            None => {
+                let span = impl_header.last().map_or(Span::call_site(), TokenTree::span);
+                return Err(Error::new(span, "expected `;` after the impl header"));
+            }
+        }
+    }
+    expand(impl_header.into_iter().collect(), iter.collect())
+}
+
+/// Generate a trait from a blanket impl block, the inverse of [`macro@blanket_trait`].
+///
+/// Takes the trait name, with an optional visibility, generics and supertraits.
+/// `#[blanket(...)]` attributes on the impl apply to the trait.
+///
+/// ```
+/// # use blanket_trait::blanket_trait_from;
+/// trait Name {
+///     fn name(&self) -> &str;
+/// }
+///
+/// #[blanket_trait_from(pub Greet)]
+/// impl<T: Name> Greet for T {
+///     fn greet(&self) -> String {
+///         format!("hello {}", self.name())
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn blanket_trait_from(trait_head: TokenStream, tokens: TokenStream) -> TokenStream {
+    expand_from(trait_head, tokens).unwrap_or_else(Error::into_compile_error)
+}
+
+fn expand_from(trait_head: TokenStream, tokens: TokenStream) -> Result<TokenStream> {
+    let mut iter = tokens.into_iter().peekable();
+    let (directives, attrs): (Vec<_>, Vec<_>) = parse_outer(&mut iter)?.into_iter().partition(Attribute::is_blanket);
+    let mut impl_header = Vec::new();
+    attrs_to_tokens(&attrs, &mut impl_header);
+    impl_header.extend(iter);
+    let body = match impl_header.pop() {
+        Some(TokenTree::Group(body)) if body.delimiter() == Delimiter::Brace => body,
+        tt => {
+            let span = tt.map_or(Span::call_site(), |tt| tt.span());
+            return Err(Error::new(span, "expected an impl block"));
+        }
+    };
+    let mut head = trait_head.into_iter().peekable();
+    let mut item = Vec::new();
+    attrs_to_tokens(&directives, &mut item);
+    if let Some(pub_token) = head.next_if(|tt| tt.is_ident("pub")) {
+        let span = pub_token.span();
+        item.push(pub_token);
+        item.extend(head.next_if(|tt| matches!(tt, TokenTree::Group(g) if g.delimiter() == Delimiter::Parenthesis)));
+        item.push(ident("trait", span));
+    } else {
+        item.push(ident("trait", body.span()));
+    }
+    item.extend(head);
+    item.push(TokenTree::Group(body));
+    expand(impl_header.into_iter().collect(), item.into_iter().collect())
+}
+
+/// Generate a marker trait with a blanket implementation from a placeholder struct.
+///
+/// `#[blanket(trait = Name, over = "T: Bounds")]` generates `trait Name: Bounds {}` with the visibility
+/// of the struct and `impl<T: Bounds> Name for T {}`, so `Name` can be used in place of `Bounds`. The struct itself is not emitted again,
+/// its documentation and `#[cfg]` attributes are moved to the trait.
+/// Other `#[blanket(...)]` directives apply to the trait, as with [`macro@blanket_trait`].
+///
+/// ```
+/// # use blanket_trait::BlanketTrait;
+/// #[derive(BlanketTrait)]
+/// #[blanket(trait = Printable, over = "T: std::fmt::Display + Send")]
+/// pub struct PrintableMarker;
+///
+/// fn print(value: impl Printable) {
+///     println!("{value}");
+/// }
+///
+/// print(1);
+/// ```
+#[proc_macro_derive(BlanketTrait, attributes(blanket))]
+pub fn blanket_trait_derive(input: TokenStream) -> TokenStream {
+    expand_derive(input).unwrap_or_else(Error::into_compile_error)
+}
+
+fn expand_derive(input: TokenStream) -> Result<TokenStream> {
+    let mut iter = input.into_iter().peekable();
+    let mut attrs = parse_outer(&mut iter)?;
+    let vis: Vec<_> = iter.by_ref().take_while(|tt| !tt.is_ident("struct")).collect();
+    let span = iter.next().map_or(Span::call_site(), |tt| tt.span());
+    let mut name = None;
+    let mut over = None;
+    let mut directives = vec![ident("allow_empty", span)];
+    for directive in take_directives(&mut attrs)? {
+        match directive.name.to_string().as_str() {
+            "trait" => name = Some(directive.expect_ident()?),
+            "over" => over = Some(directive.expect_str()?),
+            _ => {
+                directives.push(punct(',', span));
+                directive.to_tokens(&mut directives);
+            }
+        }
+    }
+    let (Some(name), Some(over)) = (name, over) else {
+        return Err(Error::new(span, "expected `#[blanket(trait = Name, over = \"T: Bounds\")]`"));
+    };
+    let params: Vec<TokenTree> = match str_value(&over).parse::<TokenStream>() {
+        Ok(params) => params.into_iter().collect(),
+        Err(_) => Vec::new(),
+    };
+    let (param, bounds) = match params.as_slice() {
+        [TokenTree::Ident(param)] => (param, &[][..]),
+        [TokenTree::Ident(param), colon, bounds @ ..]
+            if colon.is(':') && split_commas(bounds.iter().cloned().collect()).len() == 1 =>
+        {
+            (param, bounds)
+        }
+        _ => {
+            return Err(Error::new(over.span(), "expected a type parameter and its bounds, e.g. `\"T: Clone\"`"));
+        }
+    };
+    let mut impl_header = vec![ident("impl", span), punct('<', span)];
+    impl_header.extend(params.iter().cloned());
+    impl_header.extend([
+        punct('>', span),
+        TokenTree::Ident(name.clone()),
+        ident("for", span),
+        TokenTree::Ident(param.clone()),
+    ]);
+    let directives = [ident("blanket", span), parenthesized(directives.into_iter().collect(), span)];
+    let mut item = Vec::new();
+    attrs.retain(|attr| attr.is_cfg() || attr.name().as_deref() == Some("doc"));
+    attrs.push(Attribute::new(directives.into_iter().collect(), span));
+    attrs_to_tokens(&attrs, &mut item);
+    item.extend(vis);
+    item.extend([ident("trait", span), TokenTree::Ident(name)]);
+    if !bounds.is_empty() {
+        item.push(punct(':', span));
+        item.extend(bounds.iter().cloned());
+    }
+    item.push(braced(TokenStream::new(), span));
+    expand(impl_header.into_iter().collect(), item.into_iter().collect())
+}
+
+fn expand(impl_header: TokenStream, tokens: TokenStream) -> Result<TokenStream> {
+    let headers = ImplHeader::parse_list(impl_header)?;
+    let item = ItemTrait::parse(tokens)?;
+    generate(headers, item)
+}
+
+fn generate(mut headers: Vec<ImplHeader>, mut item: ItemTrait) -> Result<TokenStream> {
+    let options = TraitOptions::parse(take_directives(&mut item.attrs)?, &item.ident)?;
+    apply_map(&options, &mut item)?;
+    if item.items.is_empty() && !options.allow_empty {
+        return Err(Error::new(
+            item.brace_span,
+            "the trait has no items to implement, use `#[blanket(allow_empty)]` for a marker trait",
+        ));
+    }
+    apply_trait_attrs(&options, &mut item, &mut headers);
+    let vis = item.vis.clone();
+    if options.module.is_some() {
+        item.vis = module::nested_vis(&vis, item.trait_token.span());
+    }
+    check_headers(&options, &mut headers, &item)?;
+    // `where_self` adds predicates on `Self` below.
+    let self_calls_recurse =
+        options.deny_self_recursion && options.where_self.is_none() && self_calls_recurse(&headers, &item);
+    add_pointer_impls(&options, &mut headers)?;
+    let trait_cfg = if options.cfg_trait {
+        ImplHeader::cfg_any(&headers, item.trait_token.span())
+    } else {
+        None
+    };
+    // Conditional compilation of the trait applies to everything generated with it.
+    let mut cfgs: Vec<_> = item.attrs.iter().filter(|attr| attr.is_cfg()).cloned().collect();
+    for header in &mut headers {
+        header.attrs.extend(cfgs.iter().cloned());
+    }
+    cfgs.extend(trait_cfg.clone());
+    if !options.no_auto_derived {
+        for header in &mut headers {
+            if !header.attrs.iter().any(|attr| attr.name().as_deref() == Some("automatically_derived")) {
+                let span = header.impl_token.span();
+                header.attrs.push(Attribute::new(ident("automatically_derived", span).into(), span));
+            }
+        }
+    }
+    let reexports = reexports(&options, &headers)?;
+    let base = match &options.base {
+        Some(alias) => base_use(alias, &headers)?,
+        None => Vec::new(),
+    };
+    let seal = match options.sealed {
+        true => seal::seal(&mut item, &headers, &cfgs),
+        false => Vec::new(),
+    };
+    add_predicates(&options, &mut headers, &mut item)?;
+    let header_params: Vec<_> = headers.iter().map(ImplHeader::type_params).collect();
+    let impl_params = header_params.concat();
+    let trait_params = generic_names(&item.generics);
+    let mut unbounded: Vec<_> = headers.iter().flat_map(ImplHeader::unbounded_type_params).collect();
+    unbounded.retain(|name| !trait_params.contains(name));
+    if let Some(only) = &options.only {
+        check_only(only, &item)?;
+    }
+    // `dyn Trait` must specify the associated types.
+    let object_types: Vec<_> = item
+        .items
+        .iter()
+        .filter(|trait_item| trait_item.kind == ItemKind::Type && trait_item.generic_params().is_empty())
+        .filter_map(|trait_item| Some(trait_item.ident.as_ref()?.to_string()))
+        .collect();
+    // Directives can add `#[cfg]` attributes, parse them before grouping.
+    let item_options: Vec<_> = item.items.iter_mut().map(ItemOptions::parse).collect::<Result<_>>()?;
+    let cfg_groups = cfg_groups(&item.items);
+    let mut bound_names = idents(&[item.supertraits.as_slice(), &item.where_clause].concat());
+    for header in &headers {
+        let tokens = [header.path.as_slice(), &header.generics, &header.self_ty, &header.where_clause];
+        bound_names.extend(idents(&tokens.concat()));
+    }
+    let checks = ItemChecks {
+        options: &options,
+        headers: &headers,
+        header_params: &header_params,
+        impl_params: &impl_params,
+        trait_params: &trait_params,
+        bound_names: &bound_names,
+        unbounded: &unbounded,
+        self_calls_recurse,
+    };
+    let mut items = Items::default();
+    let mut names = HashSet::new();
+    for (mut trait_item, item_options) in std::mem::take(&mut item.items).into_iter().zip(item_options) {
+        let alternate = cfg_alternate(&mut trait_item, &cfg_groups, &mut names)?;
+        checks.check(&trait_item)?;
+        // Only the first definition of a `#[cfg]` group is declared.
+        let mut discarded = Items::default();
+        let out = match alternate {
+            true => &mut discarded,
+            false => &mut items,
+        };
+        emit_item(&options, &impl_params, trait_item, &item_options, out)?;
+        items.impl_items.append(&mut discarded.impl_items);
+        items.helper_impl_items.append(&mut discarded.helper_impl_items);
+        items.inherent_items.append(&mut discarded.inherent_items);
+    }
+    let span = item.brace_span;
+    item.attrs.extend(trait_cfg);
+    let mut tokens = Vec::new();
+    if !options.impl_only_output {
+        item.head_to_tokens(&mut tokens);
+        tokens.push(braced(items.trait_items.into_iter().collect(), span));
+    }
+    let trait_len = tokens.len();
+    if let Some(crate_path) = &options.crate_path {
+        for header in &mut headers {
+            let mut path = crate_path.clone();
+            path.extend(path_sep(crate_path[0].span()));
+            path.extend(header.last_segment().iter().cloned());
+            header.path = path;
+        }
+    }
+    let impl_items: TokenStream = items.impl_items.into_iter().collect();
+    let mut impls = Vec::new();
+    for (header, params) in headers.iter().zip(&header_params) {
+        header.to_tokens(&mut impls);
+        impls.push(braced(capture_params(impl_items.clone(), params), span));
+    }
+    let (mut helpers, mut helper_impls) = match items.helper_items.is_empty() {
+        true => (Vec::new(), Vec::new()),
+        false => helper_trait(&item, &headers, &header_params, &cfgs, items.helper_items, items.helper_impl_items),
+    };
+    let mut inherent = Vec::new();
+    if let Some(ty) = &options.default_for {
+        // The same items as inherent items of a concrete type.
+        attrs_to_tokens(&cfgs, &mut inherent);
+        inherent.push(ident("impl", span));
+        inherent.extend(ty.iter().cloned());
+        inherent.push(braced(capture_params(items.inherent_items.into_iter().collect(), &[]), span));
+    }
+    if options.scoped {
+        // Only the trait is visible, the helper trait cannot be named outside the scope.
+        impls.append(&mut helpers);
+        impls.append(&mut helper_impls);
+        impls.append(&mut inherent);
+        tokens.extend(scope(&base, impls, span));
+    } else {
+        tokens.extend(with_base(&base, impls, span));
+    }
+    if options.impl_first {
+        tokens.rotate_left(trait_len);
+    }
+    tokens.extend(helpers);
+    tokens.extend(with_base(&base, helper_impls, span));
+    tokens.extend(seal);
+    reexports_to_tokens(&options, &item, reexports, &mut tokens);
+    tokens.extend(with_base(&base, inherent, span));
+    let mut tokens: TokenStream = tokens.into_iter().collect();
+    if let Some(name) = &options.boxed {
+        tokens.extend(boxed::boxed(&item, name, &object_types, &cfgs, !options.no_auto_derived)?);
+    }
+    if let Some(name) = &options.into_box {
+        tokens.extend(boxed::into_box(&item, name, &object_types, &cfgs)?);
+    }
+    if options.object_safe {
+        tokens.extend(boxed::assert_object_safe(&item, &object_types, &cfgs)?);
+    }
+    for ty in &options.assert_impl {
+        tokens.extend(boxed::assert_impl(&item, ty, &cfgs)?);
+    }
+    if let Some(span) = options.debug {
+        debug::write(&item.ident, span, &tokens)?;
+    }
+    if let Some(name) = &options.module {
+        tokens = module::wrap(name, &vis, options.doc_hidden, tokens);
+    }
+    Ok(tokens)
+}
+
+/// Give the functions of `#[blanket(map(name = base))]` a default delegating to `base`.
+fn apply_map(options: &TraitOptions, item: &mut ItemTrait) -> Result<()> {
+    for (name, base) in &options.map {
+        let name_str = name.to_string();
+        let Some(trait_item) = item.items.iter_mut().find(|trait_item| {
+            trait_item.kind == ItemKind::Fn
+                && trait_item.default.is_none()
+                && trait_item.ident.as_ref().is_some_and(|ident| ident.to_string() == name_str)
+        }) else {
+            return Err(Error::new(
+                name.span(),
+                format!("`{name}` is not a function without a default implementation"),
+            ));
+        };
+        if base.to_string() == name_str {
+            return Err(Error::new(
+                base.span(),
+                format!("`{name}` cannot delegate to itself, call the method of the bound trait in a default"),
+            ));
+        }
+        trait_item.default = Some(vec![trait_item.delegate(base)?]);
+        trait_item.semi = None;
+        trait_item.route_attrs();
+    }
+    Ok(())
+}
+
+/// Visibility and attributes of the trait, some of which also apply to the impls.
+fn apply_trait_attrs(options: &TraitOptions, item: &mut ItemTrait, headers: &mut [ImplHeader]) {
+    if let Some(vis) = &options.vis {
+        item.vis = vis.clone();
+    }
+    if let Some(message) = &options.on_unimplemented {
+        let span = message.span();
+        let args = [ident("message", span), punct('=', span), message.clone()];
+        let mut tokens = vec![ident("diagnostic", span)];
+        tokens.extend(path_sep(span));
+        tokens.extend([ident("on_unimplemented", span), parenthesized(args.into_iter().collect(), span)]);
+        item.attrs.push(Attribute::new(tokens.into_iter().collect(), span));
+    }
+    for lints in &options.allow {
+        item.attrs.push(Attribute::allow(lints));
+        for header in &mut *headers {
+            header.attrs.push(Attribute::allow(lints));
+        }
+    }
+    if options.deny_self_recursion {
+        for header in &mut *headers {
+            let span = header.impl_token.span();
+            header.attrs.push(Attribute::deny("unconditional_recursion", span));
+        }
+    }
+    if options.doc_hidden {
+        let span = item.trait_token.span();
+        item.attrs.push(Attribute::doc_hidden(span));
+        for header in headers {
+            header.attrs.push(Attribute::doc_hidden(span));
+        }
+    }
+}
+
+/// Check the impl headers against the trait, and make them `default impl` for `#[blanket(default_impl)]`.
+fn check_headers(options: &TraitOptions, headers: &mut [ImplHeader], item: &ItemTrait) -> Result<()> {
+    let type_defaults: Vec<_> = item
+        .items
+        .iter()
+        .filter(|trait_item| trait_item.kind == ItemKind::Type)
+        .filter_map(|trait_item| Some((trait_item.ident.as_ref()?.to_string(), trait_item.default.as_ref()?.get(1..)?)))
+        .collect();
+    for header in &mut *headers {
+        header.check_trait_args(item)?;
+        if !options.allow_unused_generic {
+            header.check_params_used()?;
+        }
+        header.resolve_own_types(&item.ident, &type_defaults);
+    }
+    if !options.allow_mismatch {
+        for header in &*headers {
+            if let Some(TokenTree::Ident(name)) = header.last_segment().first()
+                && name.to_string() != item.ident.to_string()
+            {
+                return Err(Error::new(
+                    name.span(),
+                    format!("the impl must implement the trait being defined, `{}`", item.ident),
+                ));
+            }
+        }
+    }
+    if options.default_impl {
+        for header in headers {
+            let span = header.impl_token.span();
+            header.defaultness.get_or_insert_with(|| ident("default", span));
+        }
+    }
+    Ok(())
+}
+
+/// Add the impls for `&Type` and `&mut Type` of `ref_impls`, then the impls for each `smart_ptr`.
+fn add_pointer_impls(options: &TraitOptions, headers: &mut Vec<ImplHeader>) -> Result<()> {
+    let original_headers = headers.len();
+    if options.ref_impls {
+        for header in &*headers {
+            if let [TokenTree::Ident(ty)] = header.self_ty.as_slice()
+                && header.type_params().contains(&ty.to_string())
+            {
+                return Err(Error::new(
+                    ty.span(),
+                    format!("`#[blanket(ref_impls)]` conflicts with the impl for `{ty}`, which already covers `&{ty}`"),
+                ));
+            }
+        }
+        let refs: Vec<_> = headers
+            .iter()
+            .flat_map(|header| [header.for_reference(false), header.for_reference(true)])
+            .collect();
+        headers.extend(refs);
+    }
+    let ptrs: Vec<_> = options
+        .smart_ptr
+        .iter()
+        .flat_map(|ptr| headers[..original_headers].iter().map(|header| header.for_smart_ptr(ptr)))
+        .collect();
+    headers.extend(ptrs);
+    Ok(())
+}
+
+/// Paths of the bound traits to re-export for `#[blanket(reexport)]`.
+fn reexports(options: &TraitOptions, headers: &[ImplHeader]) -> Result<Vec<Vec<TokenTree>>> {
+    let mut reexports = Vec::new();
+    match &options.reexport {
+        Some(Some(path)) => reexports.push(path.clone()),
+        Some(None) => {
+            for header in headers {
+                let Some(path) = header.single_bound() else {
+                    return Err(Error::new(
+                        header.impl_token.span(),
+                        "cannot infer the trait to re-export from the impl, use `#[blanket(reexport = Trait)]`",
+                    ));
+                };
+                let same_path = |other: &Vec<TokenTree>| idents(other) == idents(&path);
+                if !reexports.iter().any(same_path) {
+                    reexports.push(path);
+                }
+            }
+        }
+        None => (),
+    }
+    Ok(reexports)
+}
+
+/// `use Bound as Base;` in the scope of the impls, for the single bound of their type parameter.
+fn base_use(alias: &Ident, headers: &[ImplHeader]) -> Result<Vec<TokenTree>> {
+    let mut bound: Option<Vec<TokenTree>> = None;
+    for header in headers {
+        let Some(path) = header.single_bound() else {
+            return Err(Error::new(
+                header.impl_token.span(),
+                "cannot infer the base trait from the impl, the type parameter needs a single trait bound",
+            ));
+        };
+        match &bound {
+            Some(bound) if idents(bound) != idents(&path) => {
+                return Err(Error::new(
+                    header.impl_token.span(),
+                    "`base` requires all impls to have the same bound",
+                ));
+            }
+            _ => bound = Some(path),
+        }
+    }
+    let span = alias.span();
+    let mut base: Vec<_> =
+        "#[allow(unused_imports)]".parse::<TokenStream>().expect("valid attribute").into_iter().collect();
+    base.push(ident("use", span));
+    base.extend(bound.into_iter().flatten());
+    base.extend([ident("as", span), TokenTree::Ident(alias.clone()), punct(';', span)]);
+    Ok(base)
+}
+
+/// Bounds the trait and the impls need for each other: `Self` predicates of the headers,
+/// `where_self`, the supertraits and `bound(...)`.
+fn add_predicates(options: &TraitOptions, headers: &mut [ImplHeader], item: &mut ItemTrait) -> Result<()> {
+    // `Self: Bounds` in a header is a requirement of the trait, the impl keeps it to satisfy the trait.
+    // Bounds using the impl's type parameters, e.g. `Self: PartialEq<T>`, only apply to the impl.
+    let mut self_predicates = Vec::new();
+    for header in &*headers {
+        let params = header.type_params();
+        for predicate in split_commas(header.where_clause.iter().skip(1).cloned().collect()) {
+            if let [this, colon, next, ..] = predicate.as_slice()
+                && this.is_ident("Self")
+                && colon.is(':')
+                && !next.is(':')
+                && !idents(&predicate).iter().any(|name| params.contains(name))
+            {
+                let key = predicate.iter().cloned().collect::<TokenStream>().to_string();
+                if !self_predicates.contains(&key) {
+                    self_predicates.push(key);
+                    item.push_predicate(predicate);
+                }
+            }
+        }
+    }
+    if let Some(bounds) = &options.where_self {
+        // Bounds on `Self` must also hold for the blanket implementations.
+        let span = bounds.span();
+        let bounds = join_bounds(bounds.stream(), span);
+        item.push_predicate([ident("Self", span), punct(':', span)].into_iter().chain(bounds.iter().cloned()));
+        for header in &mut *headers {
+            let mut predicate = header.self_ty.clone();
+            predicate.push(punct(':', span));
+            predicate.extend(bounds.iter().cloned());
+            header.push_predicate(predicate);
+        }
+    }
+    if let Some((colon, bounds)) = item.supertraits.split_first() {
+        // The blanket implementation must satisfy the supertraits.
+        for header in &mut *headers {
+            let mut predicate = header.self_ty.clone();
+            predicate.push(colon.clone());
+            predicate.extend(bounds.iter().cloned());
+            header.push_predicate(predicate);
+        }
+    }
+    if let Some(bounds) = &options.bound {
+        for header in headers {
+            let [param] = header.type_params().try_into().map_err(|params: Vec<_>| {
+                let found = match params.is_empty() {
+                    true => "none".to_string(),
+                    false => params.iter().map(|param| format!("`{param}`")).collect::<Vec<_>>().join(", "),
+                };
+                Error::new(
+                    bounds.span(),
+                    format!("`bound(...)` requires an impl with one type parameter, found {found}"),
+                )
+            })?;
+            let span = bounds.span();
+            let mut predicate = vec![ident(&param, span), punct(':', span)];
+            predicate.extend(join_bounds(bounds.stream(), span));
+            header.push_predicate(predicate);
+        }
+    }
+    Ok(())
+}
+
+/// Check that every name in `#[blanket(only(...))]` is an item with a default.
+fn check_only(only: &[Ident], item: &ItemTrait) -> Result<()> {
+    for name in only {
+        let name_str = name.to_string();
+        let is_defaulted = |trait_item: &TraitItem| {
+            trait_item.default.is_some() && trait_item.ident.as_ref().is_some_and(|ident| ident.to_string() == name_str)
+        };
+        if !item.items.iter().any(is_defaulted) {
+            return Err(Error::new(
+                name.span(),
+                format!("`{name}` is not an item with a default implementation"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Functions defined more than once, each with a default gated by `#[cfg]`, with the signature of the first.
+fn cfg_groups(items: &[TraitItem]) -> Vec<(String, Vec<TokenTree>)> {
+    let mut cfg_groups: Vec<(String, Vec<TokenTree>)> = Vec::new();
+    for trait_item in items {
+        let Some(ident) = &trait_item.ident else {
+            continue;
+        };
+        let name = ident.to_string();
+        let same_name = |other: &TraitItem| other.ident.as_ref().is_some_and(|other| other.to_string() == name);
+        let is_alternate = |other: &TraitItem| {
+            other.kind == ItemKind::Fn && other.default.is_some() && other.attrs.iter().any(Attribute::is_cfg)
+        };
+        let mut group = items.iter().filter(|other| same_name(other));
+        if group.clone().count() > 1
+            && group.clone().all(is_alternate)
+            && !cfg_groups.iter().any(|(other, _)| *other == name)
+        {
+            let sig = group.next().map(TraitItem::declared_sig).unwrap_or_default();
+            cfg_groups.push((name, sig));
+        }
+    }
+    cfg_groups
+}
+
+/// Whether the item is a later definition of a `#[cfg]` group, moving the `#[cfg]` of a group to its impl.
+/// Errors on duplicate definitions outside of a group.
+fn cfg_alternate(
+    trait_item: &mut TraitItem,
+    cfg_groups: &[(String, Vec<TokenTree>)],
+    names: &mut HashSet<(bool, String)>,
+) -> Result<bool> {
+    let Some(ident) = &trait_item.ident else {
+        return Ok(false);
+    };
+    // Associated types live in a different namespace from functions and constants.
+    let is_type = trait_item.kind == ItemKind::Type;
+    let group = cfg_groups.iter().find(|(name, _)| !is_type && *name == ident.to_string());
+    if let Some((_, sig)) = group {
+        let declared: TokenStream = trait_item.declared_sig().into_iter().collect();
+        let first: TokenStream = sig.iter().cloned().collect();
+        if declared.to_string() != first.to_string() {
+            return Err(Error::new(
+                ident.span(),
+                format!("`#[cfg]` gated definitions of `{ident}` must have the same signature `{first}`"),
+            ));
+        }
+        // The trait declares the function once for every configuration.
+        let (cfgs, attrs) = std::mem::take(&mut trait_item.attrs).into_iter().partition(Attribute::is_cfg);
+        trait_item.attrs = attrs;
+        trait_item.impl_attrs.splice(0..0, cfgs);
+    }
+    if !names.insert((is_type, ident.to_string())) {
+        if group.is_none() {
+            return Err(Error::new(ident.span(), format!("duplicate definition of `{ident}`")));
+        }
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// Checks of each trait item against the impl headers.
+struct ItemChecks<'a> {
+    options: &'a TraitOptions,
+    headers: &'a [ImplHeader],
+    header_params: &'a [Vec<String>],
+    impl_params: &'a [String],
+    trait_params: &'a [String],
+    /// Names in the headers and supertraits, e.g. single letter traits like `A` in `impl<T: A> B for T`.
+    bound_names: &'a [String],
+    unbounded: &'a [String],
+    self_calls_recurse: bool,
+}
+
+impl ItemChecks<'_> {
+    fn check(&self, trait_item: &TraitItem) -> Result<()> {
+        if trait_item.kind == ItemKind::Type
+            && let (Some(ident), Some(default)) = (&trait_item.ident, &trait_item.default)
+            && let Some(span) = find_path(default, "Self", &ident.to_string())
+        {
+            return Err(Error::new(
+                span,
+                format!("`Self::{ident}` refers to the associated type being defined"),
+            ));
+        }
+        if trait_item.kind == ItemKind::Fn
+            && let Some(konst) = trait_item
+                .sig
+                .iter()
+                .take_while(|tt| !tt.is_ident("fn"))
+                .find(|tt| tt.is_ident("const"))
+        {
+            // Stable rustc rejects `const fn` in traits and impls of traits alike.
+            return Err(Error::new(
+                konst.span(),
+                "`const fn` is not supported in traits on stable Rust, use an associated constant instead",
+            ));
+        }
+        for param in trait_item.generic_params() {
+            // The default is copied into the impl, where the parameter would shadow the impl's.
+            if let Some((name, span)) = generic_name(&param)
+                && self.impl_params.contains(&name)
+                && trait_item.default.is_some()
+            {
+                return Err(Error::new(
+                    span,
+                    format!("`{name}` shadows the type parameter `{name}` of the impl, rename one of them"),
+                ));
+            }
+        }
+        if let Some(default) = &trait_item.default {
+            // Catch bodies using a type parameter that an impl does not declare, e.g. `T::a()` with
+            // `impl B for u32`, the default is copied into every impl.
+            let method_params = param_names(&trait_item.generic_params());
+            for (header, params) in self.headers.iter().zip(self.header_params) {
+                let is_type_param = |name: &Ident, item_name: &Ident| {
+                    let name = name.to_string();
+                    !params.contains(&name)
+                        && !method_params.contains(&name)
+                        && (self.impl_params.contains(&name)
+                            || self.trait_params.contains(&name)
+                            || (looks_like_type_param(&name, item_name) && !self.bound_names.contains(&name)))
+                };
+                if let Some((name, _)) = find_path_by(default, &is_type_param) {
+                    let ty: TokenStream = header.self_ty.iter().cloned().collect();
+                    let message = match params.is_empty() {
+                        true => format!(
+                            "`{name}` is not declared by the impl for `{ty}`, \
+                             which implements the trait for a concrete type"
+                        ),
+                        false => {
+                            format!("`{name}` is not declared by the impl for `{ty}`, add it to the impl generics")
+                        }
+                    };
+                    return Err(Error::new(name.span(), message));
                 }
             }
-            trait_block.push(braced(trait_block_inner.into_iter().collect(), span));
-            impl_block.push(braced(impl_block_inner.into_iter().collect(), span));
-        } else {
-            trait_block.push(tt);
         }
+        if !self.unbounded.is_empty() && let Some(default) = &trait_item.default {
+            // Items of `T` can only come from its bounds, the rest is left to rustc.
+            let method_params = param_names(&trait_item.generic_params());
+            let is_unbounded = |name: &Ident, _: &Ident| {
+                let name = name.to_string();
+                self.unbounded.contains(&name) && !method_params.contains(&name)
+            };
+            if let Some((name, item_name)) = find_path_by(default, &is_unbounded) {
+                let message =
+                    format!("`{name}` has no bounds in the impl header, `{name}::{item_name}` cannot be resolved");
+                return Err(Error::new(name.span(), message));
+            }
+        }
+        if self.self_calls_recurse
+            && let (Some(span), Some(ident)) = (trait_item.find_self_call(), &trait_item.ident)
+        {
+            let message = format!(
+                "`self.{ident}()` calls itself recursively, use `Self::{ident}(self)` if the recursion is intended"
+            );
+            return Err(Error::new(span, message));
+        }
+        // `&Type` and `Ptr<Type>` only give shared access to the value.
+        let options = self.options;
+        let directive = match options.ref_impls {
+            true => "ref_impls",
+            false => "smart_ptr",
+        };
+        if (options.ref_impls || !options.smart_ptr.is_empty())
+            && trait_item.kind == ItemKind::Fn
+            && let Some(receiver) = trait_item.receiver()
+            && !trait_item.has_ref_receiver()
+        {
+            let receiver: TokenStream = receiver.into_iter().collect();
+            return Err(Error::new(
+                trait_item.span(),
+                format!("`#[blanket({directive})]` requires methods to take `&self`, found `{receiver}`"),
+            ));
+        }
+        Ok(())
     }
-    trait_block.into_iter().chain(impl_block).collect()
+}
+
+/// Whether `name::item_name` looks like a call through a type parameter, e.g. `T::a()`, rather than
+/// a variant or constant of a type with a single letter name, e.g. `E::One`.
+fn looks_like_type_param(name: &str, item_name: &Ident) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        && chars.next().is_none()
+        && item_name.to_string().starts_with(|c: char| c.is_lowercase())
+}
+
+/// Tokens of the trait items, by where they are emitted.
+#[derive(Default)]
+struct Items {
+    trait_items: Vec<TokenTree>,
+    impl_items: Vec<TokenTree>,
+    helper_items: Vec<TokenTree>,
+    helper_impl_items: Vec<TokenTree>,
+    inherent_items: Vec<TokenTree>,
+}
+
+/// Declare the item in the trait and define it in the impls, or in the helper trait if it is skipped.
+fn emit_item(
+    options: &TraitOptions,
+    impl_params: &[String],
+    trait_item: TraitItem,
+    item_options: &ItemOptions,
+    items: &mut Items,
+) -> Result<()> {
+    if let Some(only) = &options.only
+        && let Some(ident) = &trait_item.ident
+        && !item_options.skip
+        && !only.iter().any(|name| name.to_string() == ident.to_string())
+    {
+        // Left to the implementor like the required items of a partial trait.
+        trait_item.declaration_to_tokens(&mut items.trait_items);
+        return Ok(());
+    }
+    if trait_item.default.is_none()
+        && let Some(message) = trait_item.kind.missing_default()
+    {
+        if !options.partial {
+            let error = Error::new(trait_item.span(), message);
+            let cfgs: Vec<_> = trait_item.attrs.iter().filter(|attr| attr.is_cfg()).cloned().collect();
+            if cfgs.is_empty() {
+                return Err(error);
+            }
+            // The `#[cfg]` is evaluated after expansion, only report the error if the item is compiled.
+            attrs_to_tokens(&cfgs, &mut items.trait_items);
+            items.trait_items.extend(error.into_compile_error());
+            return Ok(());
+        }
+        // Required items of a partial trait are not implemented by the blanket impl.
+        if options.boxed.is_some() || options.into_box.is_some() || options.object_safe {
+            trait_item.check_object_safe()?;
+        }
+        trait_item.declaration_to_tokens(&mut items.trait_items);
+        return Ok(());
+    }
+    if options.strict {
+        trait_item.check_sig_matches()?;
+    }
+    if item_options.skip {
+        trait_item.declaration_to_tokens(&mut items.helper_items);
+        trait_item.into_definition_tokens(&mut items.helper_impl_items);
+        return Ok(());
+    }
+    if options.boxed.is_some() || options.into_box.is_some() || options.object_safe {
+        trait_item.check_object_safe()?;
+    }
+    trait_item.declaration_to_tokens(&mut items.trait_items);
+    if let Some(ty) = &options.default_for
+        && matches!(trait_item.kind, ItemKind::Fn | ItemKind::Const)
+    {
+        let method_params = param_names(&trait_item.generic_params());
+        let is_impl_param = |name: &Ident, _: &Ident| {
+            let name = name.to_string();
+            impl_params.contains(&name) && !method_params.contains(&name)
+        };
+        let default = trait_item.default.as_deref().unwrap_or_default();
+        if let Some((name, _)) = find_path_by(default, &is_impl_param) {
+            let ty: TokenStream = ty.iter().cloned().collect();
+            return Err(Error::new(
+                name.span(),
+                format!("`{name}` is not available in the inherent impl for `{ty}` generated by `default_for`"),
+            ));
+        }
+        trait_item.inherent_to_tokens(&[ident("pub", trait_item.span())], &mut items.inherent_items);
+    }
+    trait_item.into_definition_tokens(&mut items.impl_items);
+    Ok(())
+}
+
+/// Skipped items live on a private trait with the same blanket implementation, returns the trait and its impls.
+fn helper_trait(
+    item: &ItemTrait,
+    headers: &[ImplHeader],
+    header_params: &[Vec<String>],
+    cfgs: &[Attribute],
+    helper_items: Vec<TokenTree>,
+    helper_impl_items: Vec<TokenTree>,
+) -> (Vec<TokenTree>, Vec<TokenTree>) {
+    let span = item.brace_span;
+    let helper = Ident::new(&format!("__{}Helpers", item.ident), item.ident.span());
+    let mut helpers = Vec::new();
+    attrs_to_tokens(cfgs, &mut helpers);
+    helpers.push(item.trait_token.clone());
+    helpers.push(TokenTree::Ident(helper.clone()));
+    helpers.extend(item.generics.iter().cloned());
+    helpers.extend(item.where_clause.iter().cloned());
+    helpers.push(braced(helper_items.into_iter().collect(), span));
+    let helper_impl_items: TokenStream = helper_impl_items.into_iter().collect();
+    let mut helper_impls = Vec::new();
+    for (header, params) in headers.iter().zip(header_params) {
+        header.to_tokens_with_path(&header.last_segment_renamed(helper.clone()), &mut helper_impls);
+        helper_impls.push(braced(capture_params(helper_impl_items.clone(), params), span));
+    }
+    (helpers, helper_impls)
+}
+
+/// `pub use path::Bound;` for each re-exported trait.
+fn reexports_to_tokens(
+    options: &TraitOptions,
+    item: &ItemTrait,
+    reexports: Vec<Vec<TokenTree>>,
+    tokens: &mut Vec<TokenTree>,
+) {
+    for path in reexports {
+        // A trait named without a path is already in scope, unless the items are moved to a module.
+        let local = !path.iter().any(|tt| tt.is(':'));
+        if local && options.module.is_none() {
+            continue;
+        }
+        let span = path[0].span();
+        if options.doc_hidden {
+            attrs_to_tokens(&[Attribute::doc_hidden(span)], tokens);
+        }
+        tokens.extend(item.vis.iter().cloned());
+        tokens.push(ident("use", span));
+        if local {
+            tokens.push(ident("super", span));
+            tokens.extend(path_sep(span));
+        }
+        tokens.extend(path);
+        tokens.push(punct(';', span));
+    }
+}
+
+/// Wrap impls in `const _: () = { base impls };` if there is a `use` for `#[blanket(base = Name)]`.
+fn with_base(base: &[TokenTree], impls: Vec<TokenTree>, span: Span) -> Vec<TokenTree> {
+    match base.is_empty() {
+        true => impls,
+        false => scope(base, impls, span),
+    }
+}
+
+/// `const _: () = { prelude items };`, items in the block cannot be named outside of it.
+fn scope(prelude: &[TokenTree], items: Vec<TokenTree>, span: Span) -> Vec<TokenTree> {
+    if items.is_empty() {
+        return items;
+    }
+    let mut tokens: Vec<_> = "const _: () =".parse::<TokenStream>().expect("valid const").into_iter().collect();
+    tokens.push(braced(prelude.iter().cloned().chain(items).collect(), span));
+    tokens.push(punct(';', span));
+    tokens
+}
+
+/// Whether `self.name()` in a default of `name` can only call the method itself: every impl is for
+/// a type parameter without bounds, and neither the trait nor the impls have predicates on `Self`,
+/// so no bound trait or inherent method can supply `name`.
+fn self_calls_recurse(headers: &[ImplHeader], item: &ItemTrait) -> bool {
+    item.supertraits.is_empty()
+        && item.where_clause.is_empty()
+        && headers.iter().all(|header| {
+            let unbounded = header.unbounded_type_params();
+            matches!(header.self_ty.as_slice(), [TokenTree::Ident(ty)] if unbounded.contains(&ty.to_string()))
+                && !idents(&header.where_clause).iter().any(|name| name == "Self")
+        })
 }
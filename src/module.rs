@@ -0,0 +1,59 @@
+use proc_macro::{Ident, Span, TokenStream, TokenTree};
+
+use crate::attr::{Attribute, attrs_to_tokens};
+use crate::tokens::{TokenTreeExt, braced, ident, parenthesized, path_sep, punct};
+
+/// The visibility with the same meaning one module deeper, paths relative to the module get a `super::`.
+pub(crate) fn nested_vis(vis: &[TokenTree], span: Span) -> Vec<TokenTree> {
+    let scope = match vis {
+        [] => Vec::new(),
+        [_, TokenTree::Group(scope)] => scope.stream().into_iter().collect(),
+        _ => return vis.to_vec(),
+    };
+    let path = match scope.as_slice() {
+        [in_token, path @ ..] if in_token.is_ident("in") => path,
+        path => path,
+    };
+    let mut nested = vec![ident("super", span)];
+    match path {
+        [] => (),
+        [tt, rest @ ..] if tt.is_ident("self") => nested.extend(rest.iter().cloned()),
+        [tt, ..] if tt.is_ident("super") => {
+            nested.extend(path_sep(span));
+            nested.extend(path.iter().cloned());
+        }
+        // `pub(crate)` and `pub(in crate::...)` do not depend on the module.
+        _ => return vis.to_vec(),
+    }
+    let scope = match nested.len() {
+        1 => nested,
+        _ => [vec![ident("in", span)], nested].concat(),
+    };
+    vec![ident("pub", span), parenthesized(scope.into_iter().collect(), span)]
+}
+
+/// Wrap the generated items in `pub mod name` and re-export them with `vis`,
+/// both `#[doc(hidden)]` if `doc_hidden`.
+pub(crate) fn wrap(name: &Ident, vis: &[TokenTree], doc_hidden: bool, tokens: TokenStream) -> TokenStream {
+    let span = name.span();
+    let mut body: TokenStream = "#[allow(unused_imports)] use super::*;".parse().expect("valid import");
+    body.extend(tokens);
+    let hidden = match doc_hidden {
+        true => vec![Attribute::doc_hidden(span)],
+        false => Vec::new(),
+    };
+    let mut output = Vec::new();
+    attrs_to_tokens(&hidden, &mut output);
+    output.extend([
+        ident("pub", span),
+        ident("mod", span),
+        TokenTree::Ident(name.clone()),
+        braced(body, span),
+    ]);
+    attrs_to_tokens(&hidden, &mut output);
+    output.extend(vis.iter().cloned());
+    output.extend([ident("use", span), TokenTree::Ident(name.clone())]);
+    output.extend(path_sep(span));
+    output.extend([punct('*', span), punct(';', span)]);
+    output.into_iter().collect()
+}
@@ -0,0 +1,383 @@
+use proc_macro::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+
+use crate::attr::{Attribute, Directive, DirectiveValue, take_directives};
+use crate::error::{Error, Result};
+use crate::item::{ItemKind, TraitItem};
+use crate::seal::snake_case;
+use crate::tokens::{TokenTreeExt, ident, parenthesized, punct, split_commas, str_value};
+
+/// Directives on the trait itself.
+#[derive(Default)]
+pub(crate) struct TraitOptions {
+    /// Allow the impl headers to implement a trait with a different name.
+    pub allow_mismatch: bool,
+    /// Allow type parameters of an impl that the impl target does not use.
+    pub allow_unused_generic: bool,
+    /// Allow items without a default implementation.
+    pub partial: bool,
+    /// Apply the `#[cfg]` attributes of the impl headers to the trait.
+    pub cfg_trait: bool,
+    /// Name of the `Box<dyn Trait>` wrapper to generate.
+    pub boxed: Option<Ident>,
+    /// Name of the function converting a value to `Box<dyn Trait>`.
+    pub into_box: Option<Ident>,
+    /// Assert that the trait can be used as `dyn Trait`.
+    pub object_safe: bool,
+    /// Add `#[doc(hidden)]` to the generated items.
+    pub doc_hidden: bool,
+    /// Lints allowed on the trait and the impls, each `(...)` of `allow(...)`.
+    pub allow: Vec<Group>,
+    /// Types asserted to implement the trait.
+    pub assert_impl: Vec<Vec<TokenTree>>,
+    /// Prevent implementations other than the blanket implementations.
+    pub sealed: bool,
+    /// Write the expansion to `target/blanket_trait`, the span of the directive.
+    pub debug: Option<Span>,
+    /// Check that the trait and impl signatures of each item are identical.
+    pub strict: bool,
+    /// Deny `unconditional_recursion` on the impls, and error on `self.name()` in the default of `name`
+    /// if it can only resolve to the method itself.
+    pub deny_self_recursion: bool,
+    /// Do not add `#[automatically_derived]` to the generated impls.
+    pub no_auto_derived: bool,
+    /// Visibility of the trait replacing the declared one.
+    pub vis: Option<Vec<TokenTree>>,
+    /// Bounds added to the single type parameter of the impls.
+    pub bound: Option<Group>,
+    /// Module to generate the items in.
+    pub module: Option<Ident>,
+    /// Allow a trait without items.
+    pub allow_empty: bool,
+    /// Type to also implement the functions and constants on as inherent items.
+    pub default_for: Option<Vec<TokenTree>>,
+    /// Names of the items implemented by the impls, the others are left to the implementor.
+    pub only: Option<Vec<Ident>>,
+    /// Functions without a default delegating to a method of the same type, `(name, base_name)`.
+    pub map: Vec<(Ident, Ident)>,
+    /// Emit the impls before the trait.
+    pub impl_first: bool,
+    /// Only emit the impls, for a trait declared elsewhere.
+    pub impl_only_output: bool,
+    /// Emit the impls as `default impl`, requires the `specialization` feature.
+    pub default_impl: bool,
+    /// Message of `#[diagnostic::on_unimplemented]` on the trait.
+    pub on_unimplemented: Option<TokenTree>,
+    /// Also implement the trait for `&T` and `&mut T`.
+    pub ref_impls: bool,
+    /// Also implement the trait for these smart pointers of the self types, e.g. `::std::rc::Rc`.
+    pub smart_ptr: Vec<Vec<TokenTree>>,
+    /// Bounds on `Self` added to the where clause of the trait.
+    pub where_self: Option<Group>,
+    /// Re-export the bound trait, `Some(None)` if it is inferred from the impl headers.
+    pub reexport: Option<Option<Vec<TokenTree>>>,
+    /// Alias of the bound trait imported for the impls.
+    pub base: Option<Ident>,
+    /// Path of the module containing the trait, used by the impls to name it, e.g. `$crate::traits`.
+    pub crate_path: Option<Vec<TokenTree>>,
+    /// Place the impls and helper items in an anonymous scope.
+    pub scoped: bool,
+}
+
+impl TraitOptions {
+    pub fn parse(directives: Vec<Directive>, trait_ident: &Ident) -> Result<Self> {
+        let mut options = TraitOptions::default();
+        for directive in directives {
+            match directive.name.to_string().as_str() {
+                "allow_mismatch" => {
+                    directive.expect_flag()?;
+                    if options.sealed {
+                        return Err(Error::new(
+                            directive.span(),
+                            "`sealed` cannot be used with `allow_mismatch`",
+                        ));
+                    }
+                    options.allow_mismatch = true;
+                }
+                "allow_unused_generic" => {
+                    directive.expect_flag()?;
+                    options.allow_unused_generic = true;
+                }
+                "allow_empty" => {
+                    directive.expect_flag()?;
+                    options.allow_empty = true;
+                }
+                "partial" => {
+                    directive.expect_flag()?;
+                    options.partial = true;
+                }
+                "cfg_trait" => {
+                    directive.expect_flag()?;
+                    options.cfg_trait = true;
+                }
+                "sealed" => {
+                    directive.expect_flag()?;
+                    if options.allow_mismatch {
+                        return Err(Error::new(
+                            directive.span(),
+                            "`sealed` cannot be used with `allow_mismatch`",
+                        ));
+                    }
+                    if options.impl_only_output {
+                        return Err(Error::new(
+                            directive.span(),
+                            "`sealed` cannot be used with `impl_only_output`",
+                        ));
+                    }
+                    options.sealed = true;
+                }
+                "order" => {
+                    let order = directive.expect_str()?;
+                    options.impl_first = match str_value(&order).as_str() {
+                        "impl_first" => true,
+                        "trait_first" => false,
+                        _ => {
+                            return Err(Error::new(
+                                order.span(),
+                                "expected `\"impl_first\"` or `\"trait_first\"`",
+                            ));
+                        }
+                    };
+                }
+                "allow" => {
+                    let DirectiveValue::List(lints) = &directive.value else {
+                        return Err(Error::new(directive.span(), "expected `allow(lint, ...)`"));
+                    };
+                    options.allow.push(lints.clone());
+                }
+                "doc_hidden" => {
+                    directive.expect_flag()?;
+                    options.doc_hidden = true;
+                }
+                "object_safe" => {
+                    directive.expect_flag()?;
+                    options.object_safe = true;
+                }
+                "impl_only_output" => {
+                    directive.expect_flag()?;
+                    if options.sealed {
+                        return Err(Error::new(
+                            directive.span(),
+                            "`sealed` cannot be used with `impl_only_output`",
+                        ));
+                    }
+                    options.impl_only_output = true;
+                }
+                "default_impl" => {
+                    directive.expect_flag()?;
+                    options.default_impl = true;
+                }
+                "on_unimplemented" => options.on_unimplemented = Some(directive.expect_str()?),
+                "smart_ptr" => {
+                    let DirectiveValue::List(list) = &directive.value else {
+                        return Err(Error::new(directive.span(), "expected `smart_ptr(Arc, Rc)`"));
+                    };
+                    for ptr in split_commas(list.stream()) {
+                        let span = ptr[0].span();
+                        // `Arc` and `Rc` are not in the prelude.
+                        let path = match ptr.as_slice() {
+                            [name] if name.is_ident("Arc") => "::std::sync::Arc",
+                            [name] if name.is_ident("Rc") => "::std::rc::Rc",
+                            _ => {
+                                options.smart_ptr.push(ptr);
+                                continue;
+                            }
+                        };
+                        let path: TokenStream = path.parse().expect("valid path");
+                        options.smart_ptr.push(
+                            path.into_iter()
+                                .map(|mut tt| {
+                                    tt.set_span(span);
+                                    tt
+                                })
+                                .collect(),
+                        );
+                    }
+                }
+                "ref_impls" => {
+                    directive.expect_flag()?;
+                    options.ref_impls = true;
+                }
+                "where_self" => match directive.value {
+                    DirectiveValue::List(bounds) => options.where_self = Some(bounds),
+                    _ => return Err(Error::new(directive.span(), "expected `where_self(...)`")),
+                },
+                "base" => options.base = Some(directive.expect_ident()?),
+                "crate_path" => match directive.value {
+                    DirectiveValue::Assign(path) => options.crate_path = Some(path),
+                    _ => return Err(Error::new(directive.span(), "expected `crate_path = path::to::module`")),
+                },
+                "scoped" => {
+                    directive.expect_flag()?;
+                    options.scoped = true;
+                }
+                "reexport" => match directive.value {
+                    DirectiveValue::Flag => options.reexport = Some(None),
+                    DirectiveValue::Assign(path) => options.reexport = Some(Some(path)),
+                    DirectiveValue::List(_) => return Err(Error::new(directive.span(), "expected `reexport = Trait`")),
+                },
+                "only" => {
+                    let DirectiveValue::List(list) = &directive.value else {
+                        return Err(Error::new(directive.span(), "expected `only(...)`"));
+                    };
+                    let names = split_commas(list.stream())
+                        .into_iter()
+                        .map(|name| match name.as_slice() {
+                            [TokenTree::Ident(name)] => Ok(name.clone()),
+                            _ => Err(Error::new(name[0].span(), "expected an item name")),
+                        });
+                    options.only = Some(names.collect::<Result<_>>()?);
+                }
+                "map" => {
+                    let DirectiveValue::List(list) = &directive.value else {
+                        return Err(Error::new(directive.span(), "expected `map(name = base_name)`"));
+                    };
+                    for entry in split_commas(list.stream()) {
+                        match entry.as_slice() {
+                            [TokenTree::Ident(name), eq, TokenTree::Ident(base)] if eq.is('=') => {
+                                options.map.push((name.clone(), base.clone()));
+                            }
+                            _ => return Err(Error::new(entry[0].span(), "expected `name = base_name`")),
+                        }
+                    }
+                }
+                "default_for" => match directive.value {
+                    DirectiveValue::Assign(ty) => options.default_for = Some(ty),
+                    _ => return Err(Error::new(directive.span(), "expected `default_for = Type`")),
+                },
+                "assert_impl" => match directive.value {
+                    DirectiveValue::Assign(ty) => options.assert_impl.push(ty),
+                    _ => return Err(Error::new(directive.span(), "expected `assert_impl = Type`")),
+                },
+                "module" => options.module = Some(directive.expect_ident()?),
+                "bound" => match directive.value {
+                    DirectiveValue::List(bounds) => options.bound = Some(bounds),
+                    _ => return Err(Error::new(directive.span(), "expected `bound(...)`")),
+                },
+                "vis" => options.vis = Some(parse_vis(&directive)?),
+                "no_auto_derived" => {
+                    directive.expect_flag()?;
+                    options.no_auto_derived = true;
+                }
+                "strict" => {
+                    directive.expect_flag()?;
+                    options.strict = true;
+                }
+                "deny_self_recursion" => {
+                    directive.expect_flag()?;
+                    options.deny_self_recursion = true;
+                }
+                "debug" => {
+                    directive.expect_flag()?;
+                    options.debug = Some(directive.span());
+                }
+                "boxed" => {
+                    options.boxed = Some(match directive.value {
+                        DirectiveValue::Flag => Ident::new(&format!("Boxed{trait_ident}"), trait_ident.span()),
+                        _ => directive.expect_ident()?,
+                    });
+                }
+                "into_box" => {
+                    options.into_box = Some(match directive.value {
+                        DirectiveValue::Flag => {
+                            let name = format!("into_boxed_{}", snake_case(&trait_ident.to_string()));
+                            Ident::new(&name, trait_ident.span())
+                        }
+                        _ => directive.expect_ident()?,
+                    });
+                }
+                _ => return Err(directive.unknown()),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Parse a visibility, either unquoted or the contents of a string literal, e.g. `"pub(crate)"`,
+/// which can also be empty for a private trait.
+fn parse_vis(directive: &Directive) -> Result<Vec<TokenTree>> {
+    let (span, vis) = match &directive.value {
+        DirectiveValue::Assign(value) if !matches!(value.as_slice(), [TokenTree::Literal(_)]) => {
+            (value[0].span(), value.clone())
+        }
+        _ => {
+            let lit = directive.expect_str()?;
+            let span = lit.span();
+            let mut vis: Vec<TokenTree> = match str_value(&lit).parse::<TokenStream>() {
+                Ok(vis) => vis.into_iter().collect(),
+                Err(_) => Vec::new(),
+            };
+            for tt in &mut vis {
+                tt.set_span(span);
+            }
+            (span, vis)
+        }
+    };
+    let valid = match vis.as_slice() {
+        [] => true,
+        [pub_token] => pub_token.is_ident("pub"),
+        [pub_token, TokenTree::Group(scope)]
+            if pub_token.is_ident("pub") && scope.delimiter() == Delimiter::Parenthesis =>
+        {
+            let scope: Vec<_> = scope.stream().into_iter().collect();
+            match scope.as_slice() {
+                [tt] => ["crate", "self", "super"].iter().any(|name| tt.is_ident(name)),
+                [in_token, _, ..] => in_token.is_ident("in"),
+                _ => false,
+            }
+        }
+        _ => false,
+    };
+    if !valid {
+        return Err(Error::new(span, "expected a visibility, e.g. `pub(crate)`"));
+    }
+    Ok(vis)
+}
+
+/// Directives on an item in the trait body.
+#[derive(Default)]
+pub(crate) struct ItemOptions {
+    /// Implement the item on the helper trait instead of the generated trait.
+    pub skip: bool,
+}
+
+impl ItemOptions {
+    /// Parse and remove the directives of an item.
+    pub fn parse(item: &mut TraitItem) -> Result<Self> {
+        let mut options = ItemOptions::default();
+        for directive in take_directives(&mut item.attrs)? {
+            match directive.name.to_string().as_str() {
+                name @ ("skip" | "impl_only") => {
+                    directive.expect_flag()?;
+                    if name == "impl_only" && item.kind != ItemKind::Fn {
+                        return Err(Error::new(
+                            directive.span(),
+                            "`#[blanket(impl_only)]` is only allowed on functions",
+                        ));
+                    }
+                    if item.default.is_none() {
+                        return Err(Error::new(
+                            item.span(),
+                            format!("`#[blanket({name})]` requires a default implementation"),
+                        ));
+                    }
+                    options.skip = true;
+                }
+                "feature" => {
+                    let feature = directive.expect_str()?;
+                    let span = feature.span();
+                    let args = [ident("feature", span), punct('=', span), feature];
+                    let tokens = [ident("cfg", span), parenthesized(args.into_iter().collect(), span)];
+                    item.attrs.push(Attribute::new(tokens.into_iter().collect(), span));
+                }
+                "doc_impl" => {
+                    let doc = directive.expect_str()?;
+                    let span = doc.span();
+                    let tokens = [ident("doc", span), punct('=', span), doc];
+                    item.impl_attrs.push(Attribute::new(tokens.into_iter().collect(), span));
+                }
+                _ => return Err(directive.unknown()),
+            }
+        }
+        Ok(options)
+    }
+}
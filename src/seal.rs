@@ -0,0 +1,90 @@
+use proc_macro::{Ident, TokenStream, TokenTree};
+
+use crate::attr::{Attribute, attrs_to_tokens};
+use crate::header::ImplHeader;
+use crate::item::ItemTrait;
+use crate::tokens::{AngleDepth, TokenTreeExt, angle_bracketed, braced, ident, path_sep, punct};
+
+/// Add a private `Sealed` supertrait to the trait, the returned tokens declare it
+/// and implement it for the impl headers.
+pub(crate) fn seal(item: &mut ItemTrait, headers: &[ImplHeader], cfgs: &[Attribute]) -> Vec<TokenTree> {
+    let span = item.ident.span();
+    let module = Ident::new(&format!("__{}_seal", snake_case(&item.ident.to_string())), span);
+    let sealed = Ident::new("Sealed", span);
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for param in angle_bracketed(&item.generics) {
+        // Bounds and defaults are left to the trait.
+        let (param, arg) = if param[0].is('\'') {
+            (&param[..2], &param[..2])
+        } else if param[0].is_ident("const") {
+            let mut depth = AngleDepth::default();
+            let end = param
+                .iter()
+                .position(|tt| {
+                    depth.push(tt);
+                    depth.is_top() && tt.is('=')
+                })
+                .unwrap_or(param.len());
+            (&param[..end], &param[1..2])
+        } else {
+            (&param[..1], &param[..1])
+        };
+        params.extend(param.iter().cloned());
+        params.push(punct(',', span));
+        args.extend(arg.iter().cloned());
+        args.push(punct(',', span));
+    }
+
+    let mut tokens = Vec::new();
+    attrs_to_tokens(cfgs, &mut tokens);
+    attrs_to_tokens(&[Attribute::doc_hidden(span)], &mut tokens);
+    tokens.extend([ident("mod", span), TokenTree::Ident(module.clone())]);
+    let mut body = vec![
+        ident("pub", span),
+        ident("trait", span),
+        TokenTree::Ident(sealed.clone()),
+    ];
+    if !params.is_empty() {
+        body.push(punct('<', span));
+        body.extend(params);
+        body.push(punct('>', span));
+    }
+    body.push(braced(TokenStream::new(), span));
+    tokens.push(braced(body.into_iter().collect(), span));
+    for header in headers {
+        let mut path = vec![TokenTree::Ident(module.clone())];
+        path.extend(path_sep(span));
+        path.extend(header.last_segment_renamed(sealed.clone()));
+        header.to_tokens_with_path(&path, &mut tokens);
+        tokens.push(braced(TokenStream::new(), span));
+    }
+
+    item.supertraits.push(match item.supertraits.is_empty() {
+        true => punct(':', span),
+        false => punct('+', span),
+    });
+    item.supertraits.push(TokenTree::Ident(module));
+    item.supertraits.extend(path_sep(span));
+    item.supertraits.push(TokenTree::Ident(sealed));
+    if !args.is_empty() {
+        item.supertraits.push(punct('<', span));
+        item.supertraits.extend(args);
+        item.supertraits.push(punct('>', span));
+    }
+    tokens
+}
+
+/// Convert a `CamelCase` trait name to `snake_case`.
+pub(crate) fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_uppercase() && prev_lower {
+            snake.push('_');
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
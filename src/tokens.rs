@@ -0,0 +1,270 @@
+use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+
+pub(crate) trait TokenTreeExt {
+    fn is_brace(&self) -> bool;
+    fn is(&self, c: char) -> bool;
+    fn is_ident(&self, name: &str) -> bool;
+}
+
+impl TokenTreeExt for TokenTree {
+    fn is_brace(&self) -> bool {
+        if let TokenTree::Group(g) = self && g.delimiter() == Delimiter::Brace {
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is(&self, c: char) -> bool {
+        if let TokenTree::Punct(p) = self && p.as_char() == c {
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_ident(&self, name: &str) -> bool {
+        if let TokenTree::Ident(i) = self {
+            i.to_string() == name
+        } else {
+            false
+        }
+    }
+}
+
+/// Tracks the nesting of `<` and `>` in a token sequence.
+///
+/// The `>` of `->` is not counted.
+#[derive(Default)]
+pub(crate) struct AngleDepth {
+    depth: usize,
+    after_minus: bool,
+}
+
+impl AngleDepth {
+    pub fn push(&mut self, tt: &TokenTree) {
+        if tt.is('<') {
+            self.depth += 1;
+        } else if tt.is('>') && !self.after_minus && self.depth > 0 {
+            self.depth -= 1;
+        }
+        self.after_minus = matches!(tt, TokenTree::Punct(p) if p.as_char() == '-' && p.spacing() == Spacing::Joint);
+    }
+
+    pub fn is_top(&self) -> bool {
+        self.depth == 0
+    }
+}
+
+pub(crate) fn semi(span: Span) -> TokenTree {
+    punct(';', span)
+}
+
+pub(crate) fn punct(c: char, span: Span) -> TokenTree {
+    let mut punct = Punct::new(c, Spacing::Alone);
+    punct.set_span(span);
+    TokenTree::Punct(punct)
+}
+
+/// `::` at a span.
+pub(crate) fn path_sep(span: Span) -> [TokenTree; 2] {
+    let mut first = Punct::new(':', Spacing::Joint);
+    first.set_span(span);
+    [TokenTree::Punct(first), punct(':', span)]
+}
+
+pub(crate) fn ident(name: &str, span: Span) -> TokenTree {
+    TokenTree::Ident(Ident::new(name, span))
+}
+
+/// `'name` at a span.
+pub(crate) fn lifetime(name: &str, span: Span) -> [TokenTree; 2] {
+    let mut quote = Punct::new('\'', Spacing::Joint);
+    quote.set_span(span);
+    [TokenTree::Punct(quote), ident(name, span)]
+}
+
+pub(crate) fn braced(tokens: TokenStream, span: Span) -> TokenTree {
+    let mut braced = TokenTree::Group(Group::new(Delimiter::Brace, tokens));
+    braced.set_span(span);
+    braced
+}
+
+pub(crate) fn parenthesized(tokens: TokenStream, span: Span) -> TokenTree {
+    let mut parenthesized = TokenTree::Group(Group::new(Delimiter::Parenthesis, tokens));
+    parenthesized.set_span(span);
+    parenthesized
+}
+
+/// Parse generated code with every token at `span`, including the tokens in groups.
+pub(crate) fn parse_at(code: &str, span: Span) -> Vec<TokenTree> {
+    fn respan(tokens: TokenStream, span: Span) -> Vec<TokenTree> {
+        tokens
+            .into_iter()
+            .map(|tt| match tt {
+                TokenTree::Group(g) => {
+                    let mut group = Group::new(g.delimiter(), respan(g.stream(), span).into_iter().collect());
+                    group.set_span(span);
+                    TokenTree::Group(group)
+                }
+                mut tt => {
+                    tt.set_span(span);
+                    tt
+                }
+            })
+            .collect()
+    }
+    respan(code.parse().expect("generated code is valid"), span)
+}
+
+/// Contents of a string literal, `"..."` or a raw string `r"..."`, `r#"..."#`.
+pub(crate) fn str_value(lit: &TokenTree) -> String {
+    let lit = lit.to_string();
+    let raw = lit.strip_prefix('r').unwrap_or(&lit);
+    let hashes = raw.len() - raw.trim_start_matches('#').len();
+    raw[hashes + 1..raw.len() - hashes - 1].to_string()
+}
+
+/// Split a token stream on commas that are not nested in `<>`.
+pub(crate) fn split_commas(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut result = Vec::new();
+    let mut current = Vec::new();
+    let mut depth = AngleDepth::default();
+    for tt in stream {
+        if depth.is_top() && tt.is(',') {
+            result.push(std::mem::take(&mut current));
+            continue;
+        }
+        depth.push(&tt);
+        current.push(tt);
+    }
+    result.push(current);
+    result.retain(|segment| !segment.is_empty());
+    result
+}
+
+/// Split the contents of `<...>` on commas.
+pub(crate) fn angle_bracketed(generics: &[TokenTree]) -> Vec<Vec<TokenTree>> {
+    match generics {
+        [open, inner @ .., close] if open.is('<') && close.is('>') => split_commas(inner.iter().cloned().collect()),
+        _ => Vec::new(),
+    }
+}
+
+/// Name of a generic parameter or argument, lifetimes include the `'`.
+pub(crate) fn generic_name(param: &[TokenTree]) -> Option<(String, Span)> {
+    match param {
+        [quote, TokenTree::Ident(name), ..] if quote.is('\'') => Some((format!("'{name}"), quote.span())),
+        [konst, TokenTree::Ident(name), ..] if konst.is_ident("const") => Some((name.to_string(), name.span())),
+        [TokenTree::Ident(name), ..] => Some((name.to_string(), name.span())),
+        _ => None,
+    }
+}
+
+/// Names of generic parameters.
+pub(crate) fn param_names(params: &[Vec<TokenTree>]) -> Vec<String> {
+    params.iter().filter_map(|param| generic_name(param)).map(|(name, _)| name).collect()
+}
+
+/// Names of the generic parameters in `<...>`.
+pub(crate) fn generic_names(generics: &[TokenTree]) -> Vec<String> {
+    param_names(&angle_bracketed(generics))
+}
+
+/// Join a comma separated list of bounds with `+`.
+pub(crate) fn join_bounds(list: TokenStream, span: Span) -> Vec<TokenTree> {
+    let mut bounds = Vec::new();
+    for (i, bound) in split_commas(list).into_iter().enumerate() {
+        if i > 0 {
+            bounds.push(punct('+', span));
+        }
+        bounds.extend(bound);
+    }
+    bounds
+}
+
+/// Names of all identifiers in a token sequence, including nested groups.
+pub(crate) fn idents(tokens: &[TokenTree]) -> Vec<String> {
+    let mut names = Vec::new();
+    for tt in tokens {
+        match tt {
+            TokenTree::Ident(ident) => names.push(ident.to_string()),
+            TokenTree::Group(g) => names.extend(idents(&g.stream().into_iter().collect::<Vec<_>>())),
+            _ => (),
+        }
+    }
+    names
+}
+
+/// Replace `Self` in precise capturing lists, e.g. `impl Sized + use<'a, Self>`, with the type parameters
+/// of an impl, which cannot capture `Self` as the trait does.
+pub(crate) fn capture_params(tokens: TokenStream, params: &[String]) -> TokenStream {
+    let tokens: Vec<_> = tokens.into_iter().collect();
+    let mut output = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let tt = &tokens[i];
+        i += 1;
+        if let TokenTree::Group(g) = tt {
+            let mut group = Group::new(g.delimiter(), capture_params(g.stream(), params));
+            group.set_span(g.span());
+            output.push(TokenTree::Group(group));
+            continue;
+        }
+        output.push(tt.clone());
+        if !tt.is_ident("use") || !tokens.get(i).is_some_and(|tt| tt.is('<')) {
+            continue;
+        }
+        let Some(end) = tokens[i..].iter().position(|tt| tt.is('>')).map(|end| i + end) else {
+            continue;
+        };
+        let span = tt.span();
+        let captures = split_commas(tokens[i + 1..end].iter().cloned().collect());
+        let names = idents(&tokens[i + 1..end]);
+        let captures = captures.into_iter().flat_map(|capture| match capture.as_slice() {
+            [this] if this.is_ident("Self") => params
+                .iter()
+                .filter(|param| !names.contains(param))
+                .map(|param| vec![ident(param, span)])
+                .collect(),
+            _ => vec![capture],
+        });
+        output.push(tokens[i].clone());
+        for (j, capture) in captures.enumerate() {
+            if j > 0 {
+                output.push(punct(',', span));
+            }
+            output.extend(capture);
+        }
+        output.push(tokens[end].clone());
+        i = end + 1;
+    }
+    output.into_iter().collect()
+}
+
+/// Find the path `first::second` in a token sequence, including nested groups.
+pub(crate) fn find_path(tokens: &[TokenTree], first: &str, second: &str) -> Option<Span> {
+    find_path_by(tokens, &|a, b| a.to_string() == first && b.to_string() == second).map(|(a, _)| a.span())
+}
+
+/// Find a path `a::b` in a token sequence, including nested groups, where `matches(a, b)`.
+pub(crate) fn find_path_by(tokens: &[TokenTree], matches: &impl Fn(&Ident, &Ident) -> bool) -> Option<(Ident, Ident)> {
+    for (i, tt) in tokens.iter().enumerate() {
+        if let TokenTree::Group(g) = tt {
+            let inner: Vec<_> = g.stream().into_iter().collect();
+            if let Some(path) = find_path_by(&inner, matches) {
+                return Some(path);
+            }
+        } else if let TokenTree::Ident(first) = tt
+            && let [a, b, TokenTree::Ident(second), ..] = &tokens[i + 1..]
+            && a.is(':')
+            && b.is(':')
+            && matches(first, second)
+            // Not the tail of a longer path.
+            && !(i > 0 && tokens[i - 1].is(':'))
+        {
+            return Some((first.clone(), second.clone()));
+        }
+    }
+    None
+}
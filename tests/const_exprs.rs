@@ -0,0 +1,29 @@
+#![cfg(feature = "nightly")]
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use blanket_trait::blanket_trait;
+
+pub trait Lanes {
+    const N: usize;
+}
+
+impl Lanes for u8 {
+    const N: usize = 4;
+}
+
+// Predicates on const expressions are emitted verbatim.
+#[blanket_trait(impl<T: Lanes + Copy> Splat for T where [(); T::N]:)]
+pub trait Splat: Lanes + Sized + Copy {
+    fn splat(self) -> [Self; Self::N]
+    where
+        [(); Self::N]:,
+    {
+        [self; Self::N]
+    }
+}
+
+#[test]
+fn const_exprs() {
+    assert_eq!(1u8.splat(), [1; 4]);
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "nightly")]
+#![allow(incomplete_features)]
+#![feature(specialization)]
+
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a(&self) -> i32;
+}
+
+impl A for i32 {
+    fn a(&self) -> i32 {
+        *self
+    }
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(default_impl)]
+pub trait B {
+    fn b(&self) -> i32 {
+        self.a()
+    }
+}
+
+impl B for i32 {}
+
+impl B for u8 {
+    fn b(&self) -> i32 {
+        0
+    }
+}
+
+#[test]
+fn default_impl() {
+    assert_eq!(1.b(), 1);
+    assert_eq!(1u8.b(), 0);
+}
@@ -40,3 +40,2199 @@ pub trait Y {
         X::a(self)
     }
 }
+
+#[blanket_trait(impl<T: A> E for T)]
+pub trait E {
+    fn doubled(&self) -> i32 {
+        self.helper() * 2
+    }
+
+    #[blanket(skip)]
+    fn helper(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[blanket_trait(impl<T: A> F for T where T: Clone, impl<T: A> F for &[T] where T: Send,)]
+pub trait F {
+    fn f(&self) -> i32 {
+        T::a()
+    }
+
+    #[blanket(skip)]
+    fn g(&self) -> i32 {
+        T::a()
+    }
+}
+
+macro_rules! answer {
+    () => {
+        fn answer(&self) -> i32 {
+            42
+        }
+    };
+}
+
+#[blanket_trait(impl<T: A> G for T)]
+pub trait G {
+    answer!();
+
+    fn a(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[blanket_trait(impl<T: A> H for T)]
+pub trait H {
+    fn h(&self) -> i32 {
+        self.twice(T::a())
+    }
+
+    #[blanket(impl_only)]
+    fn twice(&self, value: i32) -> i32 {
+        value * 2
+    }
+}
+
+#[blanket_trait(impl<T: A> I for T)]
+pub trait I: Send + Sync {
+    fn i(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[blanket_trait(impl<T: A> J for T where T::AA: Send)]
+pub trait J: Clone + 'static {
+    fn j(&self) -> i32 {
+        self.clone().aa()
+    }
+}
+
+#[blanket_trait(impl<T: A> K for T where T: Clone,)]
+pub trait K {
+    fn k(&self) -> i32 {
+        self.clone().aa()
+    }
+}
+
+#[blanket_trait(impl<T: A> L for T where T: Clone, T::AA: Send + Sync, T: 'static,)]
+pub trait L: Send {
+    fn l(&self) -> i32 {
+        self.clone().aa()
+    }
+}
+
+pub trait Named {
+    fn named(&self) -> i32;
+}
+
+#[blanket_trait(impl<T: A> Named for T)]
+#[blanket(allow_mismatch)]
+pub trait NamedExt {
+    fn named(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[blanket_trait(impl<T: A> M for T)]
+pub trait M {
+    type X = T::AA;
+    type Y = Self;
+    type Z = <Self as A>::AA;
+}
+
+macro_rules! answer_trait {
+    ($name: ident) => {
+        blanket_trait::blanket_trait_impl! {
+            impl<T: A> $name for T where T: Clone;
+            pub trait $name {
+                fn answer(&self) -> i32 {
+                    T::a()
+                }
+            }
+        }
+    };
+}
+
+answer_trait!(N);
+
+pub trait Counter {
+    fn count(&self) -> i32 {
+        0
+    }
+
+    fn next_count(&self) -> i32;
+}
+
+#[blanket_trait(impl<T: A> Counter for T)]
+#[blanket(allow_mismatch, partial)]
+pub trait CounterImpl {
+    fn count(&self) -> i32;
+
+    fn next_count(&self) -> i32 {
+        self.count() + T::a()
+    }
+}
+
+#[blanket_trait(#[cfg(any())] impl<T: A> P for T)]
+#[blanket(cfg_trait)]
+pub trait P {
+    fn p(&self) -> i32 {
+        does_not_exist()
+    }
+}
+
+// `P` and its impl are both compiled out.
+pub trait P {}
+
+#[blanket_trait(impl<T: A> Gated for T)]
+#[cfg(any())]
+pub trait Gated {
+    fn gated(&self) -> i32 {
+        self.gated_helper()
+    }
+
+    #[blanket(skip)]
+    fn gated_helper(&self) -> i32 {
+        does_not_exist()
+    }
+}
+
+#[blanket_trait(#[cfg(all())] impl<T: A> Q for T, #[cfg(any())] impl<T: X> Q for T)]
+#[blanket(cfg_trait)]
+pub trait Q {
+    fn q(&self) -> i32 {
+        T::a()
+    }
+}
+
+pub fn use_q<T: Q>(value: &T) -> i32 {
+    value.q()
+}
+
+pub trait Describe {
+    fn describe(&self) -> String;
+}
+
+impl Describe for i32 {
+    fn describe(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[blanket_trait(impl<T: Describe> DynDescribe for T)]
+#[blanket(boxed = AnyDescribe)]
+pub trait DynDescribe {
+    fn text(&self) -> String {
+        self.describe()
+    }
+
+    fn by_value(self) -> String
+    where
+        Self: Sized,
+    {
+        self.describe()
+    }
+}
+
+#[test]
+fn boxed_wrapper() {
+    let boxed = AnyDescribe::from(42);
+    assert_eq!(boxed.text(), "42");
+}
+
+#[blanket_trait(impl<T: Describe> Produce for T)]
+#[blanket(boxed = AnyProduce)]
+pub trait Produce {
+    type Out: Clone = String;
+    fn produce(&self) -> Self::Out {
+        self.describe()
+    }
+}
+
+#[test]
+fn boxed_associated_types() {
+    let boxed: AnyProduce<String> = AnyProduce::from(7);
+    assert_eq!(boxed.produce(), "7");
+}
+
+mod boxed_in_macro {
+    macro_rules! boxed_in {
+        () => {
+            pub mod inner {
+                use super::super::Describe;
+                use blanket_trait::blanket_trait;
+
+                #[blanket_trait(impl<T: Describe> Show for T)]
+                #[blanket(boxed = AnyShow, into_box = into_show, object_safe)]
+                pub(in $crate::boxed_in_macro) trait Show {
+                    fn show(&self) -> String {
+                        self.describe()
+                    }
+                }
+            }
+        };
+    }
+
+    boxed_in!();
+
+    #[test]
+    fn boxed_macro_vis() {
+        assert_eq!(inner::AnyShow::from(3).show(), "3");
+        assert_eq!(inner::into_show(4).show(), "4");
+    }
+}
+
+#[blanket_trait(impl<T: Describe> IntoBox for T)]
+#[blanket(into_box)]
+pub trait IntoBox {
+    fn boxed_text(&self) -> String {
+        self.describe()
+    }
+}
+
+#[blanket_trait(impl<T: Describe> IntoBoxTyped for T)]
+#[blanket(into_box = boxed_typed)]
+pub trait IntoBoxTyped {
+    type Text = String;
+
+    fn typed_text(&self) -> Self::Text {
+        self.describe()
+    }
+}
+
+#[test]
+fn into_box() {
+    let boxed: Vec<Box<dyn IntoBox>> = vec![into_boxed_into_box(1), into_boxed_into_box(Meters(2))];
+    assert_eq!(boxed[1].boxed_text(), "2m");
+    assert_eq!(boxed_typed(3).typed_text(), "3");
+}
+
+#[blanket_trait(impl<T: A> Dangerous for T)]
+pub trait Dangerous {
+    /// # Safety
+    ///
+    /// Always safe, used to test `unsafe fn` signatures.
+    unsafe fn dangerous(&self) -> i32 {
+        T::a()
+    }
+}
+
+pub fn call_dangerous<T: Dangerous>(value: &T) -> i32 {
+    // Safety: `dangerous` has no preconditions.
+    unsafe { value.dangerous() }
+}
+
+#[blanket_trait(impl<Out: From<i32>, T: A> Convert<Out> for T)]
+pub trait Convert<Out> {
+    fn convert(&self) -> Out {
+        Out::from(T::a())
+    }
+}
+
+#[blanket_trait(impl<'a, T: A> Borrowed<'a, i32> for T)]
+pub trait Borrowed<'a, Out> {
+    fn borrowed(&self) -> &'a str {
+        ""
+    }
+}
+
+#[blanket_trait(impl<T: A> Strict for T)]
+#[blanket(strict)]
+pub trait Strict {
+    type S = T::AA;
+
+    fn strict<'a>(&'a self, value: &'a i32) -> &'a i32 {
+        value
+    }
+
+    // `mut` bindings and patterns are only kept in the impl.
+    fn strict_sum(mut self, (a, b): (i32, i32)) -> i32
+    where
+        Self: Sized + Default,
+    {
+        self = Self::default();
+        self.aa() + a + b
+    }
+}
+
+#[test]
+fn strict_signatures() {
+    assert_eq!(*Strict::strict(&0, &2), 2);
+    assert_eq!(5.strict_sum((1, 2)), 3);
+}
+
+// `#[automatically_derived]` is not added twice to the impl that already has it.
+#[blanket_trait(#[automatically_derived] #[allow(clippy::all)] impl<T: A> Derived for T, impl<T: A> Derived for Vec<T>)]
+#[cfg_attr(feature = "debug-output", blanket(debug))]
+pub trait Derived {
+    fn derived(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[blanket_trait(impl<T: A> NotDerived for T)]
+#[cfg_attr(feature = "debug-output", blanket(debug))]
+#[blanket(no_auto_derived)]
+pub trait NotDerived {
+    fn not_derived(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[cfg(feature = "debug-output")]
+#[test]
+fn automatically_derived_expansion() {
+    let code = expansion("Derived");
+    assert_eq!(code.matches("impl<T: A> Derived for").count(), 2);
+    assert_eq!(code.matches("#[automatically_derived]").count(), 2);
+    assert!(code.contains("#[automatically_derived]\n#[allow(clippy::all)]\nimpl<T: A> Derived for T "));
+    assert!(code.contains("#[automatically_derived]\nimpl<T: A> Derived for Vec<T>"));
+    let code = expansion("NotDerived");
+    assert!(!code.contains("automatically_derived"));
+}
+
+#[blanket_trait(impl<T: A> Documented for T)]
+pub trait Documented {
+    /// Returns the value of `A::a`.
+    #[blanket(doc_impl = "Delegates to `A::a`.")]
+    fn documented(&self) -> i32 {
+        T::a()
+    }
+}
+
+pub struct Config {
+    value: i32,
+}
+
+impl Config {
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+}
+
+#[blanket_trait(impl ConfigExt for Config)]
+pub trait ConfigExt {
+    fn doubled(&self) -> i32 {
+        self.value() * 2
+    }
+
+    fn generic<U: Default>(&self) -> U {
+        U::default()
+    }
+}
+
+#[test]
+fn concrete_impl() {
+    assert_eq!(Config { value: 2 }.doubled(), 4);
+}
+
+#[blanket_trait(impl<T: A> AsyncA for T)]
+#[allow(async_fn_in_trait)]
+pub trait AsyncA {
+    async fn async_a(&self) -> i32 {
+        T::a()
+    }
+}
+
+impl A for i32 {
+    type AA = ();
+
+    fn a() -> i32 {
+        1
+    }
+
+    fn aa(&self) -> i32 {
+        *self
+    }
+}
+
+#[test]
+fn async_fn() {
+    use std::task::{Context, Poll, Waker};
+    let future = std::pin::pin!(0.async_a());
+    let mut cx = Context::from_waker(Waker::noop());
+    assert_eq!(future.poll(&mut cx), Poll::Ready(1));
+}
+
+#[blanket_trait(impl<Out: From<i32>, T: A> SealedConvert<Out> for [T; 1])]
+#[blanket(sealed)]
+pub trait SealedConvert<Out>: Send {
+    fn sealed_convert(&self) -> Out {
+        Out::from(T::a())
+    }
+}
+
+#[test]
+fn sealed() {
+    let value: i64 = [0].sealed_convert();
+    assert_eq!(value, 1);
+}
+
+#[blanket_trait(impl<T: A> SelfBound for T where <T as SelfBound>::X: Clone)]
+pub trait SelfBound {
+    type X = T::AA;
+    fn self_bound(&self) -> Self::X
+    where
+        Self::X: Default,
+    {
+        Default::default()
+    }
+}
+
+#[test]
+fn self_bound() {
+    let () = 0.self_bound();
+}
+
+#[cfg(feature = "debug-output")]
+#[blanket_trait(impl<T: A> Debugged for T)]
+#[blanket(debug)]
+pub trait Debugged {
+    fn debugged(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[cfg(feature = "debug-output")]
+#[test]
+fn debug_output() {
+    assert_eq!(0.debugged(), 1);
+    let code = expansion("Debugged");
+    assert!(code.contains("pub trait Debugged {\n    fn debugged(&self) -> i32;\n}"));
+    assert!(code.contains("impl<T: A> Debugged for T {\n    fn debugged(&self) -> i32 {\n        T::a()\n    }\n}"));
+}
+
+#[blanket_trait(impl<T: A> ImplFirst for T)]
+#[cfg_attr(feature = "debug-output", blanket(debug))]
+#[blanket(order = "impl_first")]
+pub trait ImplFirst {
+    fn impl_first(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[test]
+fn impl_first() {
+    assert_eq!(0.impl_first(), 1);
+}
+
+/// The expansion written by `#[blanket(debug)]`, the tests using it run with `--features debug-output`.
+#[cfg(feature = "debug-output")]
+fn expansion(name: &str) -> String {
+    let target = match option_env!("CARGO_TARGET_DIR") {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("target"),
+    };
+    let path = target.join(format!("blanket_trait/{name}.rs"));
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("failed to read `{}`: {e}", path.display()))
+}
+
+#[cfg(feature = "debug-output")]
+#[test]
+fn impl_first_order() {
+    let code = expansion("ImplFirst");
+    let impl_pos = code.find("impl<T: A> ImplFirst for T").unwrap();
+    assert!(impl_pos < code.find("pub trait ImplFirst").unwrap());
+}
+
+pub trait Size {
+    const SIZE: usize;
+}
+
+impl Size for i32 {
+    const SIZE: usize = 4;
+}
+
+#[blanket_trait(impl<T: Size> Consts for T)]
+pub trait Consts {
+    const N: usize = T::SIZE;
+    const DOUBLED: usize = Self::N * 2;
+    const BYTES: [u8; 2] = [0; 2];
+}
+
+#[test]
+fn consts() {
+    assert_eq!(<i32 as Consts>::N, 4);
+    assert_eq!(<i32 as Consts>::DOUBLED, 8);
+    assert_eq!(<i32 as Consts>::BYTES, [0, 0]);
+}
+
+#[blanket_trait(impl<'a, T: A + 'a> RefSelf for &'a T, impl<'a, T> RefSelf for &'a mut T where T: A + 'a)]
+pub trait RefSelf {
+    fn ref_self(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[blanket_trait(impl<'a, T> PinSelf for std::pin::Pin<&'a mut T> where T: A + 'a)]
+pub trait PinSelf {
+    fn pin_self(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[test]
+fn reference_self_types() {
+    let mut value = 0;
+    assert_eq!((&value).ref_self(), 1);
+    let mut_ref = &mut value;
+    assert_eq!(mut_ref.ref_self(), 1);
+    assert_eq!(std::pin::Pin::new(&mut value).pin_self(), 1);
+}
+
+#[blanket_trait(impl<T: A> ImplTrait for T)]
+pub trait ImplTrait {
+    fn consume(&self, f: impl Fn(i32)) {
+        f(T::a())
+    }
+
+    fn values(&self) -> impl Iterator<Item = i32> {
+        std::iter::repeat_n(T::a(), 2)
+    }
+
+    fn mapped(&self, f: impl Fn(i32) -> i32 + Clone) -> impl Iterator<Item = i32> {
+        self.values().map(f)
+    }
+}
+
+#[test]
+fn impl_trait() {
+    let sum = std::cell::Cell::new(0);
+    0.consume(|value| sum.set(sum.get() + value));
+    assert_eq!(sum.get(), 1);
+    assert_eq!(0.values().sum::<i32>(), 2);
+    assert_eq!(0.mapped(|value| value * 3).collect::<Vec<_>>(), [3, 3]);
+}
+
+mod visibility {
+    use blanket_trait::blanket_trait;
+
+    use super::A;
+
+    #[blanket_trait(impl<T: A> Restricted for T)]
+    #[blanket(vis = "pub(crate)")]
+    pub trait Restricted {
+        fn restricted(&self) -> i32 {
+            T::a()
+        }
+    }
+
+    pub mod inner {
+        use blanket_trait::blanket_trait;
+
+        use crate::A;
+
+        #[blanket_trait(impl<T: A> Parent for T)]
+        #[blanket(vis = pub(super))]
+        pub trait Parent {
+            fn parent(&self) -> i32 {
+                T::a()
+            }
+        }
+
+        #[blanket_trait(impl<T: A> Scoped for T)]
+        #[blanket(vis = pub(in crate::visibility))]
+        pub trait Scoped {
+            fn scoped(&self) -> i32 {
+                T::a() + 1
+            }
+        }
+
+        // The visibilities are relative to this module, not the generated one.
+        #[blanket_trait(impl<T: A> NestedParent for T)]
+        #[blanket(module = nested_parent, vis = "pub(super)")]
+        pub trait NestedParent {
+            fn nested_parent(&self) -> i32 {
+                T::a() + 2
+            }
+        }
+
+        #[blanket_trait(impl<T: A> NestedScoped for T)]
+        #[blanket(module = nested_scoped, vis = pub(in super::inner))]
+        pub trait NestedScoped {
+            fn nested_scoped(&self) -> i32 {
+                T::a() + 3
+            }
+        }
+
+        pub(in crate::visibility) fn nested_scoped() -> i32 {
+            0.nested_scoped()
+        }
+    }
+
+    pub fn restricted() -> [i32; 4] {
+        use inner::{NestedParent, Parent, Scoped};
+        [0.parent(), 0.scoped(), 0.nested_parent(), inner::nested_scoped()]
+    }
+}
+
+#[test]
+fn vis_override() {
+    use visibility::Restricted;
+    assert_eq!(0.restricted(), 1);
+    assert_eq!(visibility::restricted(), [1, 2, 3, 4]);
+}
+
+#[blanket_trait(impl<T: A + Sized> Builder for T)]
+pub trait Builder: Sized {
+    fn keep(self) -> Self {
+        self
+    }
+
+    fn replaced(self, value: Self) -> Self {
+        let _ = self;
+        value
+    }
+
+    fn reset(self) -> Self
+    where
+        Self: Default,
+    {
+        Self::default()
+    }
+}
+
+#[test]
+fn self_returning() {
+    assert_eq!(3.keep(), 3);
+    assert_eq!(3.replaced(4).keep(), 4);
+    assert_eq!(3.reset(), 0);
+}
+
+#[blanket_trait(impl<T: A> Bounded for T, impl<U: A> Bounded for Vec<U>)]
+#[blanket(bound(Clone, Send + Sync))]
+pub trait Bounded {
+    fn bounded(&self) -> Self {
+        self.clone()
+    }
+}
+
+#[blanket_trait(impl<T: A> Left for T)]
+#[blanket(module = left)]
+pub trait Left {
+    fn side(&self) -> i32 {
+        self.left_helper()
+    }
+
+    #[blanket(skip)]
+    fn left_helper(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[blanket_trait(impl<T: A> Right for T)]
+#[blanket(module = right)]
+trait Right {
+    fn side(&self) -> i32 {
+        self.right_helper()
+    }
+
+    #[blanket(skip)]
+    fn right_helper(&self) -> i32 {
+        T::a() + 1
+    }
+}
+
+#[test]
+fn modules() {
+    assert_eq!(left::Left::side(&0), 1);
+    assert_eq!(Right::side(&0), 2);
+}
+
+#[blanket_trait(impl<T> Hrtb for T where for<'a> &'a T: IntoIterator<Item = &'a i32>, for<'a, 'b> &'a T: PartialEq<&'b T>)]
+pub trait Hrtb {
+    fn total(&self) -> i32 {
+        self.into_iter().sum()
+    }
+
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+#[test]
+fn higher_ranked_bounds() {
+    assert_eq!(vec![1, 2].total(), 3);
+    assert!(vec![1].same(&vec![1]));
+}
+
+#[blanket_trait(impl<T: A + Send> Marker for T)]
+#[blanket(allow_empty)]
+pub trait Marker {}
+
+pub fn use_marker<T: Marker>(_: &T) {}
+
+#[blanket_trait(impl<T: A> GenericMethods for T)]
+pub trait GenericMethods {
+    fn map<U, F: Fn(i32) -> U>(&self, f: F) -> U
+    where
+        U: Clone,
+    {
+        f(T::a()).clone()
+    }
+
+    fn repeat<const N: usize>(&self) -> [i32; N] {
+        [T::a(); N]
+    }
+
+    fn parse<U>(&self, text: &str) -> Option<U>
+    where
+        U: std::str::FromStr,
+        U::Err: std::fmt::Debug,
+    {
+        text.parse().ok()
+    }
+}
+
+#[test]
+fn generic_methods() {
+    assert_eq!(0.map(|value| value.to_string()), "1");
+    assert_eq!(0.repeat::<3>(), [1, 1, 1]);
+    assert_eq!(0.parse::<u8>("7"), Some(7));
+}
+
+#[blanket_trait::blanket_trait_from(pub FromImpl<Out>: Send)]
+#[blanket(sealed)]
+impl<Out: From<i32>, T: A + Send> FromImpl<Out> for T {
+    type Base = T::AA;
+
+    fn converted(&self) -> Out {
+        Out::from(T::a())
+    }
+}
+
+#[test]
+fn from_impl_block() {
+    let value: i64 = 0.converted();
+    assert_eq!(value, 1);
+}
+
+// `#[inline]` and `#[cold]` on the trait declaration would warn as unused.
+#[blanket_trait(impl<T: A> Inlined for T)]
+pub trait Inlined {
+    /// Documented on both.
+    #[inline]
+    fn inlined(&self) -> i32 {
+        T::a()
+    }
+
+    #[cold]
+    #[track_caller]
+    fn cold(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[blanket_trait(impl<T: A + Clone + 'static> Recursive for T)]
+pub trait Recursive {
+    fn boxed(&self) -> Box<dyn Recursive> {
+        Box::new(self.clone())
+    }
+
+    fn many(&self) -> Vec<Box<dyn Recursive>> {
+        vec![self.boxed(), self.boxed()]
+    }
+
+    fn value(&self) -> i32 {
+        T::a()
+    }
+
+    fn sum_with(&self, other: &dyn Recursive) -> i32 {
+        self.value() + other.value()
+    }
+}
+
+#[test]
+fn recursive_dyn() {
+    let many = 0.many();
+    assert_eq!(many.len(), 2);
+    assert_eq!(0.sum_with(&*many[0]), 2);
+}
+
+pub struct Meters(i32);
+
+impl Describe for Meters {
+    fn describe(&self) -> String {
+        format!("{}m", self.0)
+    }
+}
+
+#[blanket_trait(impl<T: Describe> Shout for T)]
+#[blanket(default_for = Meters)]
+pub trait Shout {
+    const VOLUME: u8 = 11;
+
+    fn shout(&self) -> String {
+        self.describe().to_uppercase()
+    }
+}
+
+#[test]
+fn default_for() {
+    assert_eq!(Meters(3).shout(), "3M");
+    assert_eq!(Meters::VOLUME, 11);
+    assert_eq!(Shout::shout(&Meters(3)), "3M");
+}
+
+#[blanket_trait(impl<T, U> Pair for (T, U) where T: A, U: A + Clone)]
+pub trait Pair {
+    fn first(&self) -> i32 {
+        T::a()
+    }
+
+    fn second(&self) -> i32 {
+        self.1.clone().aa() + U::a()
+    }
+}
+
+#[test]
+fn tuple_impl() {
+    assert_eq!((0, 2).first(), 1);
+    assert_eq!((0, 2).second(), 3);
+}
+
+pub trait Tally {
+    fn one(&self) -> i32 {
+        10
+    }
+
+    fn two(&self) -> i32;
+
+    fn three(&self) -> i32 {
+        30
+    }
+
+    fn four(&self) -> i32;
+}
+
+#[blanket_trait(impl<T: A> Tally for T)]
+#[blanket(allow_mismatch, only(two, four))]
+pub trait TallyImpl {
+    fn one(&self) -> i32 {
+        1
+    }
+
+    fn two(&self) -> i32 {
+        2
+    }
+
+    fn three(&self) -> i32 {
+        3
+    }
+
+    fn four(&self) -> i32 {
+        4
+    }
+}
+
+#[test]
+fn only_listed() {
+    assert_eq!(Tally::one(&0) + Tally::two(&0) + Tally::three(&0) + Tally::four(&0), 46);
+}
+
+pub mod base {
+    pub trait Base {
+        fn base(&self) -> i32;
+    }
+
+    impl Base for i32 {
+        fn base(&self) -> i32 {
+            *self
+        }
+    }
+}
+
+pub mod api {
+    use blanket_trait::blanket_trait;
+
+    #[blanket_trait(impl<T: crate::base::Base + ?Sized + 'static> Api for T)]
+    #[blanket(reexport)]
+    pub trait Api {
+        fn api(&self) -> i32 {
+            self.base() + 1
+        }
+    }
+
+    #[blanket_trait(impl<T: Api + std::fmt::Debug> ApiExt for T)]
+    #[blanket(reexport = std::fmt::Debug)]
+    pub trait ApiExt {
+        fn api_ext(&self) -> i32 {
+            self.api() * 2
+        }
+    }
+}
+
+#[test]
+fn reexport() {
+    use api::{Api, ApiExt, Base, Debug};
+    assert_eq!(0.base(), 0);
+    assert_eq!(0.api(), 1);
+    assert_eq!(0.api_ext(), 2);
+    assert_eq!(format!("{:?}", &0 as &dyn Debug), "0");
+}
+
+#[blanket_trait(impl<T: A + Size> SelfCalls for T)]
+pub trait SelfCalls {
+    const TWICE: usize = Self::SIZE * 2;
+
+    fn self_calls(&self) -> i32 {
+        Self::a() + <Self as A>::a() + Self::TWICE as i32
+    }
+
+    fn aa(&self) -> i32 {
+        // `aa` is also an item of `A`, qualify to pick one.
+        <Self as A>::aa(self) + 1
+    }
+}
+
+#[test]
+fn self_calls() {
+    assert_eq!(0.self_calls(), 10);
+    assert_eq!(SelfCalls::aa(&2), 3);
+}
+
+#[blanket_trait(impl<T: A> WhereSelf for T)]
+#[blanket(where_self(Clone, Sized))]
+pub trait WhereSelf {
+    fn cloned(&self) -> Self {
+        self.clone()
+    }
+}
+
+pub fn requires_clone<T: WhereSelf>(value: &T) -> T {
+    // `T: Clone` follows from the trait's where clause.
+    value.clone()
+}
+
+#[test]
+fn where_self() {
+    assert_eq!(5.cloned(), 5);
+    assert_eq!(requires_clone(&6), 6);
+}
+
+#[blanket_trait(impl<T: A> Platform for T)]
+pub trait Platform {
+    #[cfg(unix)]
+    fn separator(&self) -> char {
+        '/'
+    }
+    #[cfg(not(unix))]
+    fn separator(&self) -> char {
+        '\\'
+    }
+    #[cfg(any())]
+    fn never(&self) -> i32 {
+        0
+    }
+    #[cfg(not(any()))]
+    fn never(&self) -> i32 {
+        1
+    }
+}
+
+#[test]
+fn cfg_bodies() {
+    assert_eq!(0.separator(), std::path::MAIN_SEPARATOR);
+    assert_eq!(Platform::never(&0), 1);
+}
+
+#[blanket_trait(impl<T: A> BoundedType for T where T::AA: Clone + Default)]
+pub trait BoundedType {
+    type Item: Clone + Default = T::AA;
+    type Pair<U: Clone>: Clone = (T::AA, U);
+    fn item(&self) -> Self::Item {
+        Self::Item::default()
+    }
+}
+
+fn cloned_item<T: BoundedType>(value: &T) -> T::Item {
+    // `Clone` is known from the declaration in the trait.
+    value.item().clone()
+}
+
+#[test]
+fn bounded_type() {
+    let () = cloned_item(&0);
+    let _: <i32 as BoundedType>::Pair<u8> = ((), 0);
+}
+
+#[blanket_trait(impl<T: AsRef<[i32]>> Slice for T)]
+pub trait Slice {
+    type Iter<'a>: Iterator<Item = &'a i32>
+    where
+        Self: 'a
+        = std::slice::Iter<'a, i32>;
+    type Chunks<'a>: Iterator<Item = &'a [i32]>
+        = std::slice::Chunks<'a, i32>
+    where
+        Self: 'a;
+    fn iter(&self) -> Self::Iter<'_> {
+        self.as_ref().iter()
+    }
+    fn chunks(&self) -> Self::Chunks<'_> {
+        self.as_ref().chunks(2)
+    }
+}
+
+fn sum<S: Slice>(slice: &S) -> i32 {
+    // The bounds of the GATs are declared in the trait.
+    slice.iter().sum::<i32>() + slice.chunks().count() as i32
+}
+
+#[test]
+fn gat() {
+    assert_eq!(sum(&vec![1, 2, 3]), 8);
+    assert_eq!(Slice::iter(&[4, 5]).count(), 2);
+}
+
+#[blanket_trait(impl<T> Render for Vec<T>, impl Render for Meters)]
+#[blanket(ref_impls)]
+pub trait Render {
+    fn render(&self) -> String {
+        format!("{}", size_of::<Self>())
+    }
+
+    #[allow(clippy::needless_arbitrary_self_type)]
+    fn either<'a>(self: &'a Self, other: &'a Self, first: bool) -> &'a Self {
+        if first { self } else { other }
+    }
+}
+
+fn render_owned<R: Render>(value: R) -> String {
+    value.render()
+}
+
+#[test]
+fn ref_impls() {
+    let mut value = vec![1u8];
+    // The reference impls render the size of the reference.
+    let pointer = size_of::<&u8>().to_string();
+    assert_eq!(render_owned(&value), pointer);
+    assert_eq!(render_owned(&mut value), pointer);
+    assert_eq!(render_owned(value), size_of::<Vec<u8>>().to_string());
+    let meters = Meters(1);
+    assert_eq!(render_owned(&meters), pointer);
+    // `self: &'a Self` on the impl for `&Meters`.
+    let (first, second) = (&meters, &Meters(2));
+    assert_eq!(Render::either(&first, &second, false).0, 2);
+    assert_eq!(render_owned(meters), "4");
+}
+
+#[blanket_trait(impl<T: A> Qualified for T)]
+pub trait Qualified {
+    fn aa(&self) -> i32 {
+        // Calls on the bound trait are not recursive.
+        T::aa(self) + <T as A>::aa(self) + A::aa(self)
+    }
+
+    fn qualified_twice(&self) -> i32 {
+        Qualified::aa(self) * 2
+    }
+}
+
+#[test]
+fn qualified_calls() {
+    assert_eq!(Qualified::aa(&1), 3);
+    assert_eq!(1.qualified_twice(), 6);
+}
+
+pub mod paths {
+    use blanket_trait::blanket_trait;
+
+    #[blanket_trait(impl<T: crate::A> crate::paths::Full for T where <T as crate::paths::Full>::Output: Copy)]
+    #[blanket(sealed, reexport)]
+    pub trait Full {
+        type Output = i32;
+
+        fn full(&self) -> Self::Output {
+            crate::A::aa(self) + <T as crate::A>::a()
+        }
+    }
+}
+
+#[test]
+fn qualified_paths() {
+    use paths::Full;
+    assert_eq!(1.full(), 2);
+    assert_eq!(<i32 as paths::A>::a(), 1);
+}
+
+#[blanket_trait(impl<T: A<AA = ()>> UnitItem for T)]
+#[blanket(reexport)]
+pub trait UnitItem {
+    fn unit(&self) -> Option<()> {
+        // Only compiles with the equality constraint on `AA`.
+        let unit: T::AA = ();
+        Some(unit)
+    }
+}
+
+#[blanket_trait(impl<T> UnitWhere for T where T: A<AA = ()>)]
+pub trait UnitWhere {
+    type Item = T::AA;
+
+    fn unit_where(&self) -> Self::Item {
+        self.unit().unwrap_or_default()
+    }
+}
+
+#[test]
+fn associated_type_equality() {
+    assert_eq!(0.unit(), Some(()));
+    let () = 0.unit_where();
+}
+
+#[blanket_trait(impl<T: A> Diagnosed for T)]
+#[blanket(on_unimplemented = "`{Self}` must implement `A`")]
+pub trait Diagnosed {
+    fn diagnosed(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[test]
+fn on_unimplemented() {
+    assert_eq!(0.diagnosed(), 1);
+}
+
+pub struct Wrapper<T>(T);
+
+#[blanket_trait(impl<T: A> Delegate for Wrapper<T>)]
+pub trait Delegate {
+    fn delegate(&self) -> i32 {
+        self.0.aa() + T::aa(&self.0)
+    }
+
+    fn rewrapped(&self) -> Wrapper<i32> {
+        Wrapper(self.delegate())
+    }
+}
+
+#[test]
+fn wrapper_delegation() {
+    let wrapper = Wrapper(2);
+    assert_eq!(wrapper.delegate(), 4);
+    assert_eq!(wrapper.rewrapped().delegate(), 8);
+}
+
+#[blanket_trait(impl<T: A> Receivers for T)]
+pub trait Receivers {
+    fn pinned(self: std::pin::Pin<&mut Self>) -> i32 {
+        self.aa() + T::a()
+    }
+
+    fn boxed_self(self: Box<Self>) -> i32 {
+        T::aa(&self)
+    }
+
+    fn rc_self(self: std::rc::Rc<Self>) -> i32 {
+        self.as_ref().aa() * 2
+    }
+}
+
+#[test]
+fn receivers() {
+    let mut value = 2;
+    assert_eq!(std::pin::Pin::new(&mut value).pinned(), 3);
+    assert_eq!(Box::new(2).boxed_self(), 2);
+    assert_eq!(std::rc::Rc::new(2).rc_self(), 4);
+    // The receivers keep the trait object safe.
+    let boxed: Box<dyn Receivers> = Box::new(3);
+    assert_eq!(boxed.boxed_self(), 3);
+}
+
+#[blanket_trait(impl<T: A> Features for T)]
+pub trait Features {
+    fn always(&self) -> bool {
+        true
+    }
+
+    #[blanket(feature = "debug-output")]
+    fn with_feature(&self) -> bool {
+        true
+    }
+
+    #[cfg(not(feature = "debug-output"))]
+    fn with_feature(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn feature_items() {
+    assert!(0.always());
+    assert_eq!(0.with_feature(), cfg!(feature = "debug-output"));
+}
+
+#[blanket_trait(impl<T: AsRef<str>> Parse for T)]
+pub trait Parse {
+    type Err = std::num::ParseIntError;
+
+    fn parse_int(&self) -> Result<i32, Self::Err> {
+        let value: i32 = self.as_ref().trim().parse()?;
+        Ok(value * 2)
+    }
+
+    fn parse_sum(&self, other: &str) -> Result<i32, Self::Err> {
+        Ok(self.parse_int()? + other.parse_int()?)
+    }
+}
+
+#[test]
+fn question_mark() {
+    assert_eq!(" 4 ".parse_int(), Ok(8));
+    assert_eq!(String::from("1").parse_sum("2"), Ok(6));
+    assert!("x".parse_int().is_err());
+}
+
+/// Types that can be displayed and sent.
+#[derive(blanket_trait::BlanketTrait)]
+#[blanket(trait = Printable, over = "T: std::fmt::Display + Send")]
+#[blanket(sealed)]
+pub struct PrintableMarker;
+
+#[blanket_trait(impl<T: std::fmt::Display + Send> PrintableAttr for T)]
+#[blanket(allow_empty, sealed)]
+pub trait PrintableAttr {}
+
+#[derive(blanket_trait::BlanketTrait)]
+#[blanket(trait = Cloned, over = r"T: Clone", vis = r#"pub(crate)"#)]
+pub struct ClonedMarker;
+
+fn cloned<T: Cloned>(value: &T) -> T {
+    value.clone()
+}
+
+fn printed<T: Printable>(value: T) -> String {
+    // `Display` is a supertrait of the derived trait.
+    value.to_string()
+}
+
+fn printed_attr<T: PrintableAttr + std::fmt::Display>(value: T) -> String {
+    value.to_string()
+}
+
+#[test]
+fn derive() {
+    assert_eq!(printed(1), "1");
+    assert_eq!(printed("a"), printed_attr("a"));
+    assert_eq!(cloned(&1), 1);
+}
+
+#[blanket_trait(impl<T: A> Opaque for T)]
+pub trait Opaque {
+    fn formatted(&self) -> String {
+        format!("{}-{value}", T::a(), value = self.aa())
+    }
+
+    fn closures(&self) -> Vec<i32> {
+        let add = |n: i32| n + self.aa() + T::a();
+        let nested = || {
+            let inner = || {
+                macro_rules! twice {
+                    ($e:expr) => {
+                        $e * 2
+                    };
+                }
+                twice!(T::a())
+            };
+            inner()
+        };
+        vec![add(1), nested()]
+    }
+
+    fn blocks(&self) -> i32 {
+        let base = if self.aa() > 0 { T::a() } else { -T::a() };
+        {
+            match self.aa() {
+                0 => base,
+                n => n + { T::a() },
+            }
+        }
+    }
+}
+
+#[test]
+fn opaque_bodies() {
+    assert_eq!(2.formatted(), "1-2");
+    assert_eq!(2.closures(), [4, 2]);
+    assert_eq!(2.blocks(), 3);
+    assert_eq!(0.blocks(), -1);
+}
+
+#[blanket_trait(impl<'a, 'b, T: A> Outlives for &'a mut &'b T where T: 'b, 'b: 'a, T: Sync)]
+#[blanket(where_self(Send))]
+pub trait Outlives: Sized {
+    fn outlives(&self) -> i32 {
+        self.aa()
+    }
+}
+
+#[test]
+fn lifetime_predicates() {
+    let value = 3;
+    let mut shared = &value;
+    let unique = &mut shared;
+    assert_eq!(unique.outlives(), 3);
+}
+
+pub mod declared {
+    pub trait Declared {
+        fn declared(&self) -> i32;
+
+        fn provided(&self) -> i32 {
+            0
+        }
+    }
+}
+
+#[blanket_trait(impl<T: A> declared::Declared for T)]
+#[blanket(impl_only_output)]
+pub trait Declared {
+    fn declared(&self) -> i32 {
+        self.aa() * 10
+    }
+}
+
+#[test]
+fn impl_only_output() {
+    use declared::Declared;
+    assert_eq!(2.declared(), 20);
+    assert_eq!(2.provided(), 0);
+}
+
+pub struct Shown<T>(T);
+
+#[blanket_trait(impl<T: A> std::fmt::Display for Shown<T>)]
+#[blanket(impl_only_output)]
+pub trait Display {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "shown {}", self.0.aa())
+    }
+}
+
+#[test]
+fn foreign_trait() {
+    assert_eq!(Shown(2).to_string(), "shown 2");
+}
+
+pub trait TypeName {
+    const NAME: &'static str;
+}
+
+impl TypeName for i32 {
+    const NAME: &'static str = "i32";
+}
+
+#[blanket_trait(impl<T: TypeName + Size> NamedConsts for T)]
+pub trait NamedConsts {
+    type Item = usize;
+    const NAME: &'static str = T::NAME;
+    const DEFAULT: Self::Item = T::SIZE;
+    const LABEL: (&'static str, Self::Item) = (<Self as NamedConsts>::NAME, Self::DEFAULT + 1);
+}
+
+#[test]
+fn named_consts() {
+    assert_eq!(<i32 as NamedConsts>::NAME, "i32");
+    assert_eq!(<i32 as NamedConsts>::DEFAULT, 4);
+    assert_eq!(<i32 as NamedConsts>::LABEL, ("i32", 5));
+}
+
+pub trait Text {
+    fn text(&self) -> &str;
+}
+
+impl Text for str {
+    fn text(&self) -> &str {
+        self
+    }
+}
+
+impl Text for String {
+    fn text(&self) -> &str {
+        self
+    }
+}
+
+#[blanket_trait(impl<T: Text + ?Sized> TextLen for T)]
+pub trait TextLen {
+    fn text_len(&self) -> usize {
+        self.text().len()
+    }
+}
+
+#[blanket_trait(impl<T> TextUpper for T where T: Text + ?Sized)]
+pub trait TextUpper {
+    fn text_upper(&self) -> String {
+        T::text(self).to_uppercase()
+    }
+}
+
+#[test]
+fn unsized_impl() {
+    assert_eq!("abc".text_len(), 3);
+    assert_eq!("abc".text_upper(), "ABC");
+    let owned = String::from("ab");
+    let text: &dyn Text = &owned;
+    // `dyn Text` is unsized and implements `Text`.
+    assert_eq!(text.text_len(), 2);
+}
+
+#[blanket_trait(impl<T: A> ObjectSafe for T)]
+#[blanket(object_safe)]
+pub trait ObjectSafe {
+    type Output = T::AA;
+
+    fn object_safe(&self) -> i32 {
+        self.aa()
+    }
+
+    fn generic<U: Default>(&self) -> U
+    where
+        Self: Sized,
+    {
+        U::default()
+    }
+}
+
+#[test]
+fn object_safe() {
+    let object: &dyn ObjectSafe<Output = ()> = &3;
+    assert_eq!(object.object_safe(), 3);
+    assert_eq!(3.generic::<u8>(), 0);
+}
+
+#[blanket_trait(impl<T: AsRef<[i32]>> Captures for T)]
+pub trait Captures {
+    fn elided(&self) -> impl Iterator<Item = i32> + '_ {
+        self.as_ref().iter().copied()
+    }
+
+    fn named<'a>(&'a self, skip: usize) -> impl Iterator<Item = &'a i32> + 'a {
+        self.as_ref().iter().skip(skip)
+    }
+
+    fn precise(&self) -> impl Iterator<Item = i32> + use<'_, Self> {
+        self.as_ref().iter().map(|n| n * 2)
+    }
+}
+
+#[test]
+fn captures() {
+    let values = vec![1, 2, 3];
+    assert_eq!(values.elided().sum::<i32>(), 6);
+    assert_eq!(values.named(1).count(), 2);
+    assert_eq!(values.precise().collect::<Vec<_>>(), [2, 4, 6]);
+}
+
+pub trait Settings: Sized {
+    fn set(&mut self, value: i32);
+
+    fn setting(&self) -> i32;
+}
+
+impl Settings for i32 {
+    fn set(&mut self, value: i32) {
+        *self = value;
+    }
+
+    fn setting(&self) -> i32 {
+        *self
+    }
+}
+
+#[blanket_trait(impl<T: Settings> Chain for T)]
+pub trait Chain: Sized {
+    fn with(mut self, value: i32) -> Self {
+        self.set(value);
+        self
+    }
+
+    fn then(self, value: i32) -> Self {
+        self.with(value + 1)
+    }
+
+    fn map_with(self, f: impl FnOnce(Self) -> Self) -> Self {
+        let build = move || f(self);
+        build()
+    }
+}
+
+#[test]
+fn chain() {
+    assert_eq!(0i32.with(1).then(2).setting(), 3);
+    assert_eq!(0i32.map_with(|value| value.with(4)).setting(), 4);
+}
+
+// The trait declares `fn advance(self, step: i32)`, which would be an error with `mut`.
+#[blanket_trait(impl<T: Settings + Copy> Stepper for T)]
+pub trait Stepper: Sized {
+    fn advance(mut self, mut step: i32) -> Self {
+        step *= 2;
+        self.set(self.setting() + step);
+        self
+    }
+
+    fn advance_boxed(mut self: Box<Self>) -> i32 {
+        self.set(10);
+        self.setting()
+    }
+}
+
+#[test]
+fn mut_bindings() {
+    assert_eq!(1i32.advance(2).setting(), 5);
+    assert_eq!(Box::new(1i32).advance_boxed(), 10);
+}
+
+// Patterns are declared as `_`, e.g. `fn pair(&self, _: (i32, i32)) -> i32`.
+#[blanket_trait(impl<T: Settings> Patterns for T)]
+pub trait Patterns {
+    fn pair(&self, (a, b): (i32, i32)) -> i32 {
+        self.setting() + a * b
+    }
+
+    fn first(&self, &[first, ..]: &[i32; 2], Shown(shown): Shown<i32>) -> i32 {
+        self.setting() + first + shown
+    }
+}
+
+#[test]
+fn patterns() {
+    assert_eq!(1i32.pair((2, 3)), 7);
+    assert_eq!(1i32.first(&[4, 5], Shown(1)), 6);
+}
+
+#[blanket_trait(impl<T: A> SelfPredicates for T where Self: Clone, T: Default, Self: PartialEq<T>)]
+pub trait SelfPredicates {
+    fn duplicate(&self) -> (Self, Self)
+    where
+        Self: Sized,
+    {
+        (self.clone(), T::default())
+    }
+}
+
+fn duplicate<T: SelfPredicates>(value: &T) -> (T, T) {
+    // `T: SelfPredicates` implies `T: Clone` through the trait's where clause.
+    (value.duplicate().0, value.clone())
+}
+
+#[test]
+fn self_predicates() {
+    assert_eq!(duplicate(&2), (2, 2));
+}
+
+macro_rules! many_methods {
+    ($($name:ident)*) => {
+        #[blanket_trait(impl<T: A> ManyMethods for T)]
+        pub trait ManyMethods {
+            $(
+                fn $name(&self) -> i32 {
+                    T::a()
+                }
+            )*
+        }
+    };
+}
+
+many_methods! {
+    m00 m01 m02 m03 m04 m05 m06 m07 m08 m09
+    m10 m11 m12 m13 m14 m15 m16 m17 m18 m19
+    m20 m21 m22 m23 m24 m25 m26 m27 m28 m29
+    m30 m31 m32 m33 m34 m35 m36 m37 m38 m39
+    m40 m41 m42 m43 m44 m45 m46 m47 m48 m49
+    m50 m51 m52 m53 m54 m55 m56 m57 m58 m59
+    m60 m61 m62 m63 m64 m65 m66 m67 m68 m69
+    m70 m71 m72 m73 m74 m75 m76 m77 m78 m79
+    m80 m81 m82 m83 m84 m85 m86 m87 m88 m89
+    m90 m91 m92 m93 m94 m95 m96 m97 m98 m99
+}
+
+#[test]
+fn many_methods() {
+    assert_eq!(0.m00() + 0.m99(), 2);
+}
+
+// Unfulfilled expectations and `#[must_use]` in the impl would fail the test under `-D warnings`.
+#[blanket_trait(impl<T: A> Routed for T)]
+pub trait Routed {
+    /// Documented on both.
+    #[expect(clippy::identity_op)]
+    const ROUTED: i32 = 1 + 0;
+
+    /// Documented on both.
+    #[expect(unused_parens)]
+    type Nested = (Vec<i32>);
+
+    #[must_use]
+    #[inline]
+    #[expect(clippy::let_and_return)]
+    fn routed(&self) -> i32 {
+        let value = Self::ROUTED;
+        value
+    }
+}
+
+#[test]
+fn routed_attributes() {
+    let nested: <i32 as Routed>::Nested = Vec::new();
+    assert!(nested.is_empty());
+    assert_eq!(0.routed(), 1);
+}
+
+pub struct Red;
+pub struct Green;
+pub struct Blue;
+
+impl Red {
+    fn hex(&self) -> &'static str {
+        "#f00"
+    }
+}
+
+impl Green {
+    fn hex(&self) -> &'static str {
+        "#0f0"
+    }
+}
+
+impl Blue {
+    fn hex(&self) -> &'static str {
+        "#00f"
+    }
+}
+
+#[blanket_trait(impl Color for Red, Green, Blue)]
+pub trait Color {
+    fn css(&self) -> String {
+        format!("color: {}", self.hex())
+    }
+}
+
+#[test]
+fn concrete_types() {
+    assert_eq!(Red.css(), "color: #f00");
+    assert_eq!(Green.css(), "color: #0f0");
+    assert_eq!(Blue.css(), "color: #00f");
+}
+
+#[blanket_trait(impl Shade for Red, Green,)]
+pub trait Shade {
+    fn shade(&self) -> &'static str {
+        self.hex()
+    }
+}
+
+#[blanket_trait(impl<T: A> Trailing for T,)]
+pub trait Trailing {
+    fn trailing(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[test]
+fn trailing_comma() {
+    assert_eq!(Red.shade(), "#f00");
+    assert_eq!(Green.shade(), "#0f0");
+    assert_eq!(0.trailing(), 1);
+}
+
+#[blanket_trait(impl<I, U> Summed for I where I: Iterator<Item = U> + Clone, U: Into<i64>)]
+pub trait Summed {
+    fn summed(&self) -> i64 {
+        self.clone().map(Into::into).sum()
+    }
+}
+
+#[blanket_trait(impl<F, U> Called for F where F: Fn() -> U, U: Into<i64>)]
+#[blanket(allow_unused_generic)]
+pub trait Called {
+    fn called(&self) -> i64 {
+        self().into()
+    }
+}
+
+#[test]
+fn projection_params() {
+    assert_eq!([1u8, 2, 3].into_iter().summed(), 6);
+    assert_eq!((|| 4u8).called(), 4);
+}
+
+#[blanket_trait(impl<T: A> Asserted for T, impl Asserted for str)]
+#[blanket(assert_impl = i32, assert_impl = str)]
+pub trait Asserted {
+    fn asserted(&self) -> i32 {
+        1
+    }
+}
+
+#[test]
+fn assert_impl() {
+    assert_eq!("".asserted(), 0.asserted());
+}
+
+#[blanket_trait(impl<T: A> Hidden for T)]
+#[cfg_attr(feature = "debug-output", blanket(debug))]
+#[blanket(doc_hidden, module = hidden)]
+pub trait Hidden {
+    fn hidden(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[test]
+fn doc_hidden() {
+    assert_eq!(0.hidden(), 1);
+}
+
+#[cfg(feature = "debug-output")]
+#[test]
+fn doc_hidden_expansion() {
+    let code = expansion("Hidden");
+    assert!(code.contains("#[doc(hidden)]\npub trait Hidden"));
+    assert_eq!(code.matches("#[doc(hidden)]").count(), 2);
+}
+
+pub mod long {
+    pub mod path {
+        pub trait VeryLongTraitName {
+            fn long(&self) -> i32;
+        }
+
+        impl VeryLongTraitName for i32 {
+            fn long(&self) -> i32 {
+                *self * 100
+            }
+        }
+    }
+}
+
+#[blanket_trait(impl<T: long::path::VeryLongTraitName> Aliased for T)]
+#[blanket(base = Base)]
+pub trait Aliased {
+    fn aliased(&self) -> i32 {
+        Base::long(self) + 1
+    }
+
+    #[blanket(skip)]
+    fn helper(&self) -> i32 {
+        Base::long(self)
+    }
+}
+
+#[test]
+fn base_alias() {
+    assert_eq!(2.aliased(), 201);
+}
+
+// Would conflict with the helper trait of `Scoped` if it was not in an anonymous scope.
+#[allow(non_camel_case_types, dead_code)]
+struct __ScopedHelpers;
+
+#[blanket_trait(impl<T: A> Scoped for T)]
+#[blanket(scoped)]
+pub trait Scoped {
+    fn scoped(&self) -> i32 {
+        self.scoped_helper() + 1
+    }
+
+    #[blanket(skip)]
+    fn scoped_helper(&self) -> i32 {
+        T::a()
+    }
+}
+
+#[test]
+fn scoped() {
+    assert_eq!(0.scoped(), 2);
+}
+
+#[blanket_trait(impl<T: A> TestOnly for T)]
+pub trait TestOnly {
+    fn in_all_builds(&self) -> i32 {
+        T::a()
+    }
+
+    #[cfg(test)]
+    fn test_only(&self) -> i32 {
+        self.in_all_builds() + 1
+    }
+
+    // Removed before expansion in tests, so the missing body is not reported.
+    #[cfg(not(test))]
+    fn not_in_tests(&self) -> i32;
+}
+
+#[test]
+fn cfg_test_items() {
+    assert_eq!(0.test_only(), 2);
+}
+
+pub trait Storage: Sized {
+    fn peek(&self) -> i32;
+
+    fn poke(&mut self, value: i32);
+
+    fn absorb(self, extra: i32) -> i32;
+
+    fn create(value: i32) -> Self;
+}
+
+impl Storage for i32 {
+    fn peek(&self) -> i32 {
+        *self
+    }
+
+    fn poke(&mut self, value: i32) {
+        *self = value;
+    }
+
+    fn absorb(self, extra: i32) -> i32 {
+        self + extra
+    }
+
+    fn create(value: i32) -> Self {
+        value
+    }
+}
+
+#[blanket_trait(impl<T: Storage> Mapped for T)]
+#[blanket(map(get = peek, set = poke, into_sum = absorb, make = create))]
+pub trait Mapped: Sized {
+    fn get(&self) -> i32;
+
+    fn set(&mut self, value: i32);
+
+    fn into_sum(self, extra: i32) -> i32;
+
+    fn make(value: i32) -> Self;
+}
+
+#[test]
+fn mapped() {
+    let mut value = <i32 as Mapped>::make(1);
+    Mapped::set(&mut value, 2);
+    assert_eq!(Mapped::get(&value), 2);
+    assert_eq!(value.into_sum(3), 5);
+}
+
+pub trait Labeled {
+    fn label(&self) -> String;
+}
+
+impl Labeled for Meters {
+    fn label(&self) -> String {
+        format!("label({})", self.0)
+    }
+}
+
+#[blanket_trait(impl<T: Labeled> Greet for T)]
+#[blanket(smart_ptr(Arc, Rc))]
+pub trait Greet {
+    fn greet(&self) -> String {
+        format!("hello {}", self.label())
+    }
+
+    fn shout(&self) -> String {
+        T::label(self).to_uppercase()
+    }
+}
+
+fn greet_owned<G: Greet>(value: G) -> String {
+    value.greet()
+}
+
+#[test]
+fn smart_ptr() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+    assert_eq!(greet_owned(Arc::new(Meters(1))), "hello label(1)");
+    assert_eq!(greet_owned(Rc::new(Meters(2))), "hello label(2)");
+    assert_eq!(Rc::new(Meters(3)).shout(), "LABEL(3)");
+    assert_eq!(greet_owned(Meters(4)), "hello label(4)");
+}
+
+// `#[deprecated]` in the impl is denied by `useless_deprecated`.
+#[blanket_trait(impl<T: A> Deprecated for T)]
+#[cfg_attr(feature = "debug-output", blanket(debug))]
+pub trait Deprecated {
+    #[deprecated(note = "use `current`")]
+    fn legacy(&self) -> i32 {
+        T::a()
+    }
+
+    #[deprecated(note = "use `CURRENT`")]
+    const LEGACY: i32 = 2;
+
+    #[deprecated(note = "use `Current`")]
+    type Legacy = i32;
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated() {
+    let legacy: <i32 as Deprecated>::Legacy = 0.legacy();
+    assert_eq!(legacy + <i32 as Deprecated>::LEGACY, 3);
+}
+
+#[cfg(feature = "debug-output")]
+#[test]
+fn deprecated_declarations() {
+    let code = expansion("Deprecated");
+    let (declaration, definition) = code.split_at(code.find("impl<T: A> Deprecated for T").unwrap());
+    for note in ["use `current`", "use `CURRENT`", "use `Current`"] {
+        assert!(declaration.contains(&format!("#[deprecated(note = \"{note}\")]")));
+    }
+    assert!(!definition.contains("deprecated"));
+}
+
+pub trait Lanes {
+    const LANES: usize;
+}
+
+impl Lanes for u8 {
+    const LANES: usize = 2;
+}
+
+// Predicates on const expressions are emitted verbatim.
+#[blanket_trait(impl<T: Lanes, const N: usize> Packed for [T; N] where [(); N]:, [T; N]: Sized)]
+pub trait Packed {
+    const WIDTH: usize = N * T::LANES;
+
+    fn padded<const M: usize>(&self) -> [u8; M]
+    where
+        [(); M]:,
+    {
+        [Self::WIDTH as u8; M]
+    }
+}
+
+#[test]
+fn const_predicates() {
+    assert_eq!(<[u8; 3] as Packed>::WIDTH, 6);
+    assert_eq!([0u8; 3].padded::<2>(), [6, 6]);
+}
+
+// Without the `allow`, the unused trait and its complex signature fail the test under `-D warnings`.
+#[blanket_trait(impl<T: A> Unused for T)]
+#[cfg_attr(feature = "debug-output", blanket(debug))]
+#[blanket(allow(clippy::type_complexity, dead_code))]
+trait Unused {
+    fn complex(&self) -> Vec<Box<dyn Fn(Vec<Box<dyn Fn(i32) -> i32>>) -> Option<Vec<i32>>>> {
+        Vec::new()
+    }
+}
+
+#[cfg(feature = "debug-output")]
+#[test]
+fn allowed_lints() {
+    let code = expansion("Unused");
+    let allow = "#[allow(clippy::type_complexity, dead_code)]";
+    assert!(code.contains(&format!("{allow}\ntrait Unused")));
+    assert!(code.contains(&format!("{allow}\n#[automatically_derived]\nimpl<T: A> Unused for T")));
+}
+
+pub trait Weight {
+    fn weight(&self) -> i32;
+}
+
+impl Weight for i32 {
+    fn weight(&self) -> i32 {
+        *self
+    }
+}
+
+#[blanket_trait(impl<T: Weight + Clone> Local for T)]
+pub trait Local {
+    fn local(&self) -> i32 {
+        // Local items cannot use `T`, but can declare their own parameters.
+        fn helper<U: Weight>(value: &U) -> i32 {
+            value.weight() * SCALE
+        }
+        const SCALE: i32 = 10;
+        struct Pair<U>(U, U);
+        let pair = Pair(self.clone(), T::clone(self));
+        let offset = |value: &T| value.weight() + pair.1.weight();
+        helper(&pair.0) + offset(self)
+    }
+}
+
+#[test]
+fn local_items() {
+    assert_eq!(2.local(), 24);
+}
+
+pub mod pathed {
+    use blanket_trait::blanket_trait;
+
+    use crate::A;
+
+    pub trait Declared {
+        fn pathed(&self) -> i32;
+    }
+
+    macro_rules! measured {
+        () => {
+            #[blanket_trait(impl<T: A> Measured for T)]
+            #[blanket(crate_path = $crate::pathed)]
+            pub trait Measured {
+                fn measured(&self) -> i32 {
+                    T::a() * 5
+                }
+            }
+        };
+    }
+
+    measured!();
+}
+
+#[blanket_trait(impl<T: A> Declared for T)]
+#[blanket(impl_only_output, crate_path = crate::pathed)]
+pub trait Declared {
+    fn pathed(&self) -> i32 {
+        T::a() * 6
+    }
+}
+
+#[test]
+fn crate_path() {
+    use pathed::{Declared, Measured};
+    assert_eq!(0.measured(), 5);
+    assert_eq!(0.pathed(), 6);
+}
+
+#[blanket_trait(impl<T: A<AA: Default>> Mapper for T)]
+pub trait Mapper {
+    type Mapped<U: Clone> = Vec<(U, T::AA)>;
+    type Borrowed<'a, U: 'a>
+        = &'a [U]
+    where
+        Self: 'a;
+
+    fn map_pair<U: Clone>(&self, value: U) -> Self::Mapped<U> {
+        vec![(value, T::AA::default())]
+    }
+
+    fn reborrow<'a, U>(&'a self, values: &'a [U]) -> Self::Borrowed<'a, U> {
+        values
+    }
+}
+
+#[test]
+fn generic_associated_types() {
+    let values: <i32 as Mapper>::Mapped<&str> = 0.map_pair("a");
+    assert_eq!(values, [("a", ())]);
+    assert_eq!(0.reborrow(&[1, 2]), &[1, 2]);
+}
+
+pub trait Stack {
+    fn pop(&mut self) -> Option<i32>;
+
+    fn label(&self) -> String;
+}
+
+impl Stack for Vec<i32> {
+    fn pop(&mut self) -> Option<i32> {
+        Vec::pop(self)
+    }
+
+    fn label(&self) -> String {
+        format!("stack of {}", self.len())
+    }
+}
+
+// Methods of the bound trait take precedence over the trait's own methods.
+#[blanket_trait(impl<T: Stack> Drained for T)]
+#[blanket(deny_self_recursion)]
+pub trait Drained {
+    fn label(&self) -> String {
+        self.label()
+    }
+
+    fn drain_all(&mut self) -> usize {
+        match self.pop() {
+            Some(_) => 1 + self.drain_all(),
+            None => 0,
+        }
+    }
+}
+
+#[test]
+fn bound_self_calls() {
+    let mut stack = vec![1, 2, 3];
+    assert_eq!(Drained::label(&stack), "stack of 3");
+    assert_eq!(stack.drain_all(), 3);
+    assert!(stack.is_empty());
+}
+
+// Single letter types and supertraits are not type parameters of the impl.
+mod letters {
+    use blanket_trait::blanket_trait;
+
+    use crate::{A, Meters};
+
+    #[derive(Debug, PartialEq)]
+    pub enum E {
+        One,
+        Two,
+    }
+
+    type N = i32;
+
+    #[blanket_trait(impl Letters for i32)]
+    pub trait Letters: A {
+        fn letter(&self) -> E {
+            match A::aa(self) {
+                1 => E::One,
+                _ => E::Two,
+            }
+        }
+
+        fn largest(&self) -> N {
+            N::MAX
+        }
+    }
+
+    #[blanket_trait(impl<T: A> Mixed for Vec<T>, impl Mixed for Meters)]
+    pub trait Mixed {
+        fn code(&self) -> N {
+            E::Two as N
+        }
+    }
+}
+
+#[test]
+fn single_letter_types() {
+    use letters::{E, Letters, Mixed};
+    assert_eq!(1.letter(), E::One);
+    assert_eq!(2.letter(), E::Two);
+    assert_eq!(0.largest(), i32::MAX);
+    assert_eq!(vec![0].code(), 1);
+    assert_eq!(Meters(0).code(), 1);
+}
+
+// The supertrait and `where_self` bounds are also required of `&'__r Type` and `&'__r mut Type`.
+#[blanket_trait(impl<T: Sync> Shared for Vec<T>, impl Shared for Meters)]
+#[blanket(ref_impls, where_self(Send), sealed)]
+pub trait Shared: Sync {
+    fn shared(&self) -> usize {
+        size_of::<Self>()
+    }
+}
+
+fn shared<S: Shared>(value: S) -> usize {
+    value.shared()
+}
+
+#[test]
+fn ref_impls_with_supertraits() {
+    let mut value = vec![1u8];
+    let pointer = size_of::<&u8>();
+    assert_eq!(<&Vec<u8>>::shared(&&value), pointer);
+    assert_eq!(<&mut Vec<u8>>::shared(&&mut value), pointer);
+    assert_eq!(<&Meters>::shared(&&Meters(1)), pointer);
+    assert_eq!(shared(value), size_of::<Vec<u8>>());
+}
@@ -0,0 +1,8 @@
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    // `#[blanket(debug)]` compiles with the feature.
+    #[cfg(not(feature = "debug-output"))]
+    t.compile_fail("tests/ui/no_debug_output/*.rs");
+}
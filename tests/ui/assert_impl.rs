@@ -0,0 +1,21 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a() -> i32;
+}
+
+impl A for i32 {
+    fn a() -> i32 {
+        1
+    }
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(assert_impl = i32, assert_impl = String)]
+pub trait B {
+    fn b(&self) -> i32 {
+        T::a()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a() -> i32;
+}
+
+#[blanket_trait(impl<T: A, U> B for (T, U))]
+#[blanket(bound(Clone))]
+pub trait B {
+    fn b(&self) -> i32 {
+        T::a()
+    }
+}
+
+fn main() {}
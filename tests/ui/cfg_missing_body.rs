@@ -0,0 +1,30 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a() -> i32;
+}
+
+impl A for i32 {
+    fn a() -> i32 {
+        1
+    }
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait B {
+    fn b(&self) -> i32 {
+        T::a()
+    }
+
+    #[cfg(test)]
+    fn test_only(&self) -> i32 {
+        self.b()
+    }
+
+    #[cfg(not(test))]
+    fn missing(&self) -> i32;
+}
+
+fn main() {
+    0.test_only();
+}
@@ -0,0 +1,17 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait B {
+    #[cfg(unix)]
+    fn a(&self) -> i32 {
+        0
+    }
+    #[cfg(not(unix))]
+    fn a(&self) -> u32 {
+        0
+    }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use blanket_trait::blanket_trait;
+
+pub struct MyConfig;
+
+impl MyConfig {
+    fn value(&self) -> i32 {
+        1
+    }
+}
+
+#[blanket_trait(impl Helpers for MyConfig)]
+pub trait Helpers {
+    fn doubled(&self) -> i32 {
+        T::default() + self.value() * 2
+    }
+}
+
+fn main() {}
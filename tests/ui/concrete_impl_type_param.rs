@@ -0,0 +1,16 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a() -> i32;
+}
+
+pub struct Config;
+
+#[blanket_trait(impl<T: A> B for T, impl B for Config)]
+pub trait B {
+    fn b(&self) -> i32 {
+        T::a()
+    }
+}
+
+fn main() {}
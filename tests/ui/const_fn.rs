@@ -0,0 +1,12 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait B {
+    const fn version() -> u32 {
+        1
+    }
+}
+
+fn main() {}
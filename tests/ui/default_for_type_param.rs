@@ -0,0 +1,17 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a() -> i32;
+}
+
+pub struct Concrete;
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(default_for = Concrete)]
+pub trait B {
+    fn b(&self) -> i32 {
+        T::a()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use blanket_trait::BlanketTrait;
+
+#[derive(BlanketTrait)]
+#[blanket(trait = Marker)]
+pub struct Placeholder;
+
+fn main() {}
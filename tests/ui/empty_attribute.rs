@@ -0,0 +1,8 @@
+use blanket_trait::blanket_trait;
+
+#[blanket_trait]
+pub trait B {
+    fn b(&self) {}
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait B {}
+
+fn main() {}
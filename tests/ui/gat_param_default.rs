@@ -0,0 +1,10 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait B {
+    type Mapper<U = i32> = Vec<U>;
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {}
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(sealed, impl_only_output)]
+pub trait B {
+    fn b(&self) {}
+}
+
+fn main() {}
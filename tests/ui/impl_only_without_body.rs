@@ -0,0 +1,17 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a() -> i32;
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait B {
+    fn a(&self) -> i32 {
+        T::a()
+    }
+
+    #[blanket(impl_only)]
+    fn helper(&self) -> i32;
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a() -> i32;
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(vis = "public")]
+pub trait B {
+    fn b(&self) -> i32 {
+        T::a()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a(&self) -> i32;
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait B {
+    fn b(&self) -> i32 {
+        // Items in a body cannot use the impl's type parameter.
+        fn helper(value: &T) -> i32 {
+            value.a()
+        }
+        helper(self)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a(&self) -> i32;
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(map(b = a))]
+pub trait B {
+    fn b(&self) -> i32 {
+        self.a()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    const N: usize;
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait B {
+    const N: usize;
+}
+
+fn main() {}
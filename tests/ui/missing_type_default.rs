@@ -0,0 +1,12 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    type AA;
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait B {
+    type X;
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {}
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(object_safe)]
+pub trait B {
+    fn b(&self) -> Box<Self> {
+        unimplemented!()
+    }
+}
+
+fn main() {}
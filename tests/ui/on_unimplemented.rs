@@ -0,0 +1,15 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {}
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(on_unimplemented = "`{Self}` must implement `A` to implement `B`")]
+pub trait B {
+    fn b(&self) {}
+}
+
+fn requires_b<T: B>(_: T) {}
+
+fn main() {
+    requires_b(0);
+}
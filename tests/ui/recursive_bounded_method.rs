@@ -0,0 +1,11 @@
+use blanket_trait::blanket_trait;
+
+#[blanket_trait(impl<T: std::fmt::Display> Label for T)]
+#[blanket(deny_self_recursion)]
+pub trait Label {
+    fn label(&self) -> String {
+        self.label()
+    }
+}
+
+fn main() {}
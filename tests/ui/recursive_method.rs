@@ -0,0 +1,11 @@
+use blanket_trait::blanket_trait;
+
+#[blanket_trait(impl<T> B for T)]
+#[blanket(deny_self_recursion)]
+pub trait B {
+    fn name(&self) -> String {
+        self.name()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a(&mut self);
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(ref_impls)]
+pub trait B {
+    fn b(&mut self) {
+        self.a()
+    }
+}
+
+fn main() {}
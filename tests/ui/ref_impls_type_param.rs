@@ -0,0 +1,11 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {}
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(ref_impls)]
+pub trait B {
+    fn b(&self) {}
+}
+
+fn main() {}
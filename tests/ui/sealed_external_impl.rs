@@ -0,0 +1,25 @@
+mod sealed {
+    use blanket_trait::blanket_trait;
+
+    pub trait A {
+        fn a() -> i32;
+    }
+
+    #[blanket_trait(impl<T: A> B for T)]
+    #[blanket(sealed)]
+    pub trait B {
+        fn b(&self) -> i32 {
+            T::a()
+        }
+    }
+}
+
+struct External;
+
+impl sealed::B for External {
+    fn b(&self) -> i32 {
+        0
+    }
+}
+
+fn main() {}
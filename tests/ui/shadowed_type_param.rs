@@ -0,0 +1,12 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait B {
+    fn a<T: Default>(&self) -> T {
+        T::default()
+    }
+}
+
+fn main() {}
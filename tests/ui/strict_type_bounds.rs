@@ -0,0 +1,11 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {}
+
+#[blanket_trait(impl<T: A> B for T)]
+#[blanket(strict)]
+pub trait B {
+    type Out: Clone = i32;
+}
+
+fn main() {}
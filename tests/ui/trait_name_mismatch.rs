@@ -0,0 +1,14 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a() -> i32;
+}
+
+#[blanket_trait(impl<T: A> B for T)]
+pub trait C {
+    fn a(&self) -> i32 {
+        T::a()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use blanket_trait::blanket_trait;
+
+#[blanket_trait(impl<T> B for Vec<T>)]
+pub trait B {
+    fn b(&self) -> i32 {
+        T::a()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a() -> i32;
+}
+
+#[blanket_trait(impl<T: A> Convert<Out> for T)]
+pub trait Convert<Out: From<i32>> {
+    fn convert(&self) -> Out {
+        Out::from(T::a())
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use blanket_trait::blanket_trait;
+
+pub trait A {
+    fn a() -> i32;
+}
+
+#[blanket_trait(impl<T, U> B for (T, U) where T: A, U: A, impl<T: A> B for Vec<T>)]
+pub trait B {
+    fn b(&self) -> i32 {
+        T::a() + U::a()
+    }
+}
+
+fn main() {}